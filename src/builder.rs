@@ -0,0 +1,170 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, format_ident};
+use syn::{FnArg, ImplItem, Pat, Type};
+
+use crate::{info::{ClassInfo, to_snake_case, get_prototype_mut}, parse_class::{get_default, create_pinned, create_validated, find_method, get_error_type}};
+
+/// a setter of the builder.
+struct BuilderField {
+    name: Ident,
+    ty: Type,
+    /// the field to set after <code>new</code>, with how many <code>\_\_prototype\_\_</code> to reach it.
+    target: Option<(usize, Ident)>,
+}
+
+pub fn get_new_inputs(info: &ClassInfo) -> Option<Vec<(Ident, Type)>> {
+    let new = info._impl.as_ref().unwrap().items.iter().find_map(|item| match item {
+        ImplItem::Method(x) if x.sig.ident == "new" => Some(x),
+        _ => None,
+    })?;
+    let mut inputs = Vec::new();
+    for arg in &new.sig.inputs {
+        if let FnArg::Typed(pat_type) = arg {
+            if let Pat::Ident(ident) = pat_type.pat.as_ref() {
+                inputs.push((ident.ident.clone(), *pat_type.ty.clone()));
+            }
+        }
+    }
+    Some(inputs)
+}
+
+/// create <code>XXXBuilder</code> for class with <code>#\[builder\]</code>.
+/// 
+/// parameters of <code>new</code> are required, 
+/// fields with <code>#\[default\]</code> in the whole hierarchy are optional and set before the object is pinned,
+/// then validators run on the final values and their error is returned as <code>XXXBuildError::Invalid</code>.
+pub fn create_builder(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    let vis = info._struct.as_ref().unwrap().vis.clone();
+    let builder = format_ident!("{}Builder", name);
+
+    let inputs = match get_new_inputs(info) {
+        Some(inputs) => inputs,
+        None => {
+            let err = syn::Error::new_spanned(&name, format!("builder of {} requires a `new`", name)).to_compile_error();
+            info.extra_items.push(syn::parse2(err).unwrap());
+            return;
+        }
+    };
+    let mut builder_fields: Vec<BuilderField> = inputs.into_iter()
+        .map(|(name, ty)| BuilderField { name, ty, target: None }).collect();
+
+    let mut classes = vec![info.clone()];
+    classes.append(&mut info.get_mro());
+    for (depth, class) in classes.iter().enumerate() {
+        for field in class.get_fields() {
+            if get_default(&field).is_none() {
+                continue;
+            }
+            let id = field.ident.clone().unwrap();
+            let mut setter = id.clone();
+            if builder_fields.iter().any(|x| x.name == setter) {
+                setter = format_ident!("{}_{}", to_snake_case(&class.get_ident()), id);
+            }
+            builder_fields.push(BuilderField { name: setter, ty: field.ty.clone(), target: Some((depth, id)) });
+        }
+    }
+
+    let names: Vec<&Ident> = builder_fields.iter().map(|x| &x.name).collect();
+    let types: Vec<&Type> = builder_fields.iter().map(|x| &x.ty).collect();
+    let layouts = info.get_layouts();
+    let pointer = info.get_pointer();
+    let pointer_of_name = match info.get_shared() {
+        Some((rc, _)) => quote!{ #rc<#name> },
+        None => {
+            let _box = info.get_box();
+            quote!{ ::core::pin::Pin<#_box<#name>> }
+        },
+    };
+    let validate = find_method(info, "__validate__");
+    let build_error = format_ident!("{}BuildError", name);
+    let missing = match &validate {
+        Some(_) => quote!{ #build_error::Missing },
+        None => quote!{},
+    };
+    let mut required: Vec<TokenStream> = Vec::new();
+    let mut optional: Vec<TokenStream> = Vec::new();
+    for field in &builder_fields {
+        let field_name = &field.name;
+        match &field.target {
+            None => {
+                let err = format!("missing field `{}` for {}", field_name, name);
+                required.push(quote!{
+                    match builder.#field_name {
+                        Some(x) => x,
+                        None => return Err(#missing(#err)),
+                    }
+                });
+            },
+            Some((_, id)) if info.get_shared().is_some() => {
+                let err = syn::Error::new_spanned(id, format!("builder of shared {} can not set `{}` after `new`", name, id)).to_compile_error();
+                optional.push(err);
+            },
+            Some((depth, id)) => {
+                let target = get_prototype_mut(&layouts, *depth, quote!{ this });
+                let set = if layouts[..*depth].iter().all(|x| *x) {
+                    quote!{ #target .#id = x; }
+                } else {
+                    // a boxed prototype is not pinned before `this` is.
+                    quote!{ unsafe { #target .#id = x; } }
+                };
+                optional.push(quote!{
+                    if let Some(x) = builder.#field_name {
+                        #set
+                    }
+                });
+            },
+        }
+    }
+    let (error, create_this) = match &validate {
+        Some(validate) => {
+            let error = get_error_type(validate);
+            info.extra_items.push(syn::parse2(quote!{
+                #[derive(Debug)]
+                #vis enum #build_error {
+                    Missing(&'static str),
+                    Invalid(#error),
+                }
+            }).unwrap());
+            (quote!{ #build_error }, create_validated(info, quote!{ this }, quote!{ .map_err(#build_error::Invalid) }))
+        },
+        None => (quote!{ &'static str }, create_pinned(info, quote!{ this })),
+    };
+
+    info.extra_items.push(syn::parse2(quote!{
+        #[derive(Default)]
+        #vis struct #builder {
+            #(#names: Option<#types>),*
+        }
+    }).unwrap());
+    info.extra_items.push(syn::parse2(quote!{
+        impl #builder {
+            #(
+                pub fn #names(mut self, #names: #types) -> Self {
+                    self.#names = Some(#names);
+                    self
+                }
+            )*
+
+            pub fn build(self) -> Result<#pointer_of_name, #error> {
+                #name::__build__(self)
+            }
+        }
+    }).unwrap());
+    let _impl = info._impl.as_mut().unwrap();
+    _impl.items.push(syn::parse2(quote!{
+        pub fn builder() -> #builder {
+            #builder::default()
+        }
+    }).unwrap());
+    _impl.items.push(syn::parse2(quote!{
+        #[doc(hidden)]
+        pub fn __build__(builder: #builder) -> Result<#pointer, #error> {
+            #[allow(unused_mut)]
+            let mut this = unsafe { Self::__new_unpinned__( #(#required),* ) };
+            #(#optional)*
+            #create_this
+            Ok(this)
+        }
+    }).unwrap());
+}
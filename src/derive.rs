@@ -0,0 +1,457 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, format_ident};
+use syn::{ItemStruct, Token, punctuated::Punctuated};
+
+use crate::{info::{ClassInfo, to_snake_case}, parse_class::{create_pinned, find_method, get_borrow_init, get_default, get_unpinned_doc}};
+#[cfg(feature = "serde")]
+use crate::{CLASSES, info::Serializable};
+
+/// derives taken over by the macro, the rest are passed through.
+const DERIVES: [&str; 5] = ["Debug", "Clone", "PartialEq", "Eq", "Hash"];
+
+/// derives taken over by the macro with feature <code>serde</code>.
+const SERDE_DERIVES: [&str; 2] = ["Serialize", "Deserialize"];
+
+fn is_taken(derive: &syn::Path) -> bool {
+    derive.segments.last().is_some_and(|x| {
+        DERIVES.iter().any(|d| x.ident == d) || (cfg!(feature = "serde") && SERDE_DERIVES.iter().any(|d| x.ident == d))
+    })
+}
+
+/// whether <code>#\[derive(...)\]</code> of the struct contains <code>derive</code>.
+pub fn has_derive(info: &ClassInfo, derive: &str) -> bool {
+    info._struct.as_ref().unwrap().attrs.iter()
+        .filter_map(parse_derive)
+        .any(|x| x.iter().any(|x| x.segments.last().is_some_and(|x| x.ident == derive)))
+}
+
+fn has_method(info: &ClassInfo, method: &str) -> bool {
+    info._impl.as_ref().unwrap().items.iter().any(|x| matches!(x, syn::ImplItem::Method(x) if x.sig.ident == method))
+}
+
+fn parse_derive(attr: &syn::Attribute) -> Option<Punctuated<syn::Path, Token![,]>> {
+    if !attr.path.is_ident("derive") {
+        return None;
+    }
+    attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated).ok()
+}
+
+/// derives in <code>#\[derive(...)\]</code> of the struct which are taken over by the macro.
+fn get_derives(info: &ClassInfo) -> Vec<syn::Path> {
+    info._struct.as_ref().unwrap().attrs.iter()
+        .filter_map(parse_derive)
+        .flat_map(|x| x.into_iter().filter(is_taken))
+        .collect()
+}
+
+/// remove derives taken over by the macro from the output struct, the rest are kept.
+pub fn strip_derives(_struct: &mut ItemStruct) {
+    for attr in &mut _struct.attrs {
+        if let Some(paths) = parse_derive(attr) {
+            let rest = paths.into_iter().filter(|x| !is_taken(x));
+            attr.tokens = quote!{ ( #(#rest),* ) };
+        }
+    }
+    _struct.attrs.retain(|x| !x.path.is_ident("derive") || x.tokens.to_string() != "()");
+}
+
+/// fields of the whole hierarchy from the root class to this class, with the place to access them from <code>self</code>.
+fn get_chain_fields(info: &ClassInfo) -> Vec<(Ident, TokenStream)> {
+    let mut classes = vec![info.clone()];
+    classes.append(&mut info.get_mro());
+    let mut fields = Vec::new();
+    for (depth, class) in classes.iter().enumerate().rev() {
+        let prototypes = vec![quote!{ .__prototype__ }; depth];
+        for field in class.get_fields() {
+            let id = field.ident.clone().unwrap();
+            fields.push((id.clone(), quote!{ #(#prototypes)* .#id }));
+        }
+    }
+    fields
+}
+
+/// implement <code>Debug</code>, <code>PartialEq</code>, <code>Eq</code> and <code>Hash</code> for class with them in <code>#\[derive(...)\]</code>.
+///
+/// they cover user fields of the class and all its ancestors, <code>\_\_real\_\_</code>, <code>\_\_prototype\_\_</code> and other internal fields are skipped.
+///
+/// <code>Clone</code> will generate <code>clone\_object</code> instead, see <code>create_clone</code>.
+pub fn create_derives(info: &mut ClassInfo) {
+    let derives = get_derives(info);
+    if derives.is_empty() {
+        return;
+    }
+    let name = info.get_ident();
+    let fields = get_chain_fields(info);
+    let ids: Vec<String> = fields.iter().map(|(id, _)| id.to_string()).collect();
+    let places: Vec<&TokenStream> = fields.iter().map(|(_, place)| place).collect();
+    let generics = info._struct.as_ref().unwrap().generics.clone();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    for derive in derives {
+        let item = match derive.segments.last().unwrap().ident.to_string().as_str() {
+            "Clone" => {
+                create_clone(info);
+                continue;
+            },
+            "Serialize" => create_serialize(info),
+            "Deserialize" => {
+                create_deserialize(info);
+                continue;
+            },
+            "Debug" => {
+                let class_name = name.to_string();
+                quote!{
+                    impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+                        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                            f.debug_struct(#class_name)
+                                #(.field(#ids, &self #places))*
+                                .finish()
+                        }
+                    }
+                }
+            },
+            "PartialEq" => quote!{
+                impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics #where_clause {
+                    fn eq(&self, other: &Self) -> bool {
+                        true #(&& self #places == other #places)*
+                    }
+                }
+            },
+            "Eq" => quote!{
+                impl #impl_generics ::core::cmp::Eq for #name #ty_generics #where_clause {}
+            },
+            "Hash" => quote!{
+                impl #impl_generics ::core::hash::Hash for #name #ty_generics #where_clause {
+                    fn hash<__H__: ::core::hash::Hasher>(&self, state: &mut __H__) {
+                        #(::core::hash::Hash::hash(&self #places, state);)*
+                    }
+                }
+            },
+            _ => unreachable!(),
+        };
+        info.extra_items.push(syn::parse2(item).unwrap());
+    }
+}
+
+/// the pointer to the root trait object, which <code>clone\_dyn</code> returns.
+fn get_dyn_pointer(info: &ClassInfo) -> TokenStream {
+    let root = info.get_mro().pop().unwrap_or_else(|| info.clone());
+    let root_real = root.get_real();
+    match info.get_shared() {
+        Some((rc, _)) => quote!{ #rc<dyn #root_real> },
+        None => {
+            let _box = info.get_box();
+            quote!{ ::core::pin::Pin<#_box<dyn #root_real>> }
+        },
+    }
+}
+
+/// add <code>clone\_dyn</code> to the methods of class with <code>#\[derive(Clone)\]</code>.
+pub fn create_clone_dyn(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    let derived = has_derive(info, "Clone");
+    if info._parent.is_some() {
+        let parent = info.get_parent_info();
+        if has_method(&parent, "clone_object") && !derived {
+            let err = syn::Error::new_spanned(&name, format!("{} should derive `Clone` like {}, otherwise `clone_dyn` would only clone {}", name, parent.get_ident(), parent.get_ident())).to_compile_error();
+            info.extra_items.push(syn::parse2(err).unwrap());
+            return;
+        }
+    }
+    if !derived {
+        return;
+    }
+    let pointer = get_dyn_pointer(info);
+    info.add_items([syn::parse2(quote!{
+        fn clone_dyn(&self) -> #pointer {
+            this.clone_object()
+        }
+    }).unwrap()]);
+}
+
+/// create <code>clone\_object</code> for class with <code>#\[derive(Clone)\]</code>,
+/// which clones fields of every prototype and sets <code>\_\_real\_\_</code> of them to the new object like <code>new</code>.
+fn create_clone(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    let parent = info._parent.as_ref().map(|_| info.get_parent_info());
+    if let Some(parent) = &parent {
+        if !has_method(parent, "clone_object") {
+            let err = syn::Error::new_spanned(&name, format!("`Clone` of {} requires {} to derive `Clone`", name, parent.get_ident())).to_compile_error();
+            info.extra_items.push(syn::parse2(err).unwrap());
+            return;
+        }
+    }
+    let prototype = match parent {
+        None => quote!{},
+        Some(_) if info.is_inline() => quote!{ __prototype__: unsafe { self.__prototype__.__clone_unpinned__() }, },
+        Some(_) => quote!{ __prototype__: self.__prototype__.clone_object(), },
+    };
+    let fields: Vec<Ident> = info.get_fields().into_iter().map(|x| x.ident.unwrap()).collect();
+    let null_real = info.get_null_real();
+    let borrow = get_borrow_init(info);
+    let pointer = info.get_pointer();
+    let create_this = create_pinned(info, quote!{ unsafe { Self::__clone_unpinned__(self) } });
+    let unpinned_doc = get_unpinned_doc();
+    info._impl.as_mut().unwrap().items.push(syn::parse2(quote!{
+        #[doc(hidden)]
+        #unpinned_doc
+        #[deny(unsafe_op_in_unsafe_fn)]
+        pub unsafe fn __clone_unpinned__(&self) -> Self {
+            Self {
+                #prototype
+                __real__: #null_real,
+                _pinned: ::core::marker::PhantomPinned,
+                #borrow
+                #(#fields: ::core::clone::Clone::clone(&self.#fields)),*
+            }
+        }
+    }).unwrap());
+    info._impl.as_mut().unwrap().items.push(syn::parse2(quote!{
+        pub fn clone_object(&self) -> #pointer {
+            #create_this
+            this
+        }
+    }).unwrap());
+}
+
+/// fields of the whole hierarchy like <code>get_chain_fields</code>, with keys for serde,
+/// a field with the same name as a field of its subclass is prefixed by the class declaring it, like <code>vehicle_name</code>.
+fn get_serde_fields(info: &ClassInfo) -> Vec<(Ident, syn::Type, TokenStream)> {
+    let mut classes = vec![info.clone()];
+    classes.append(&mut info.get_mro());
+    let mut keys: Vec<Ident> = Vec::new();
+    let mut levels = Vec::new();
+    for (depth, class) in classes.iter().enumerate() {
+        let prototypes = vec![quote!{ .__prototype__ }; depth];
+        let mut fields = Vec::new();
+        for field in class.get_fields() {
+            let id = field.ident.clone().unwrap();
+            let key = if keys.contains(&id) {
+                format_ident!("{}_{}", to_snake_case(&class.get_ident()), id)
+            } else {
+                id.clone()
+            };
+            keys.push(key.clone());
+            fields.push((key, field.ty.clone(), quote!{ #(#prototypes)* .#id }));
+        }
+        levels.push(fields);
+    }
+    levels.into_iter().rev().flatten().collect()
+}
+
+/// implement <code>Serialize</code> as a struct with fields of the whole hierarchy, from the root class to this class.
+fn create_serialize(info: &ClassInfo) -> TokenStream {
+    let name = info.get_ident();
+    let class_name = name.to_string();
+    let fields = get_serde_fields(info);
+    let len = fields.len();
+    let keys: Vec<String> = fields.iter().map(|(key, _, _)| key.to_string()).collect();
+    let places: Vec<&TokenStream> = fields.iter().map(|(_, _, place)| place).collect();
+    quote!{
+        impl ::serde::Serialize for #name {
+            fn serialize<__S__: ::serde::Serializer>(&self, serializer: __S__) -> ::core::result::Result<__S__::Ok, __S__::Error> {
+                use ::serde::ser::SerializeStruct;
+                let mut state = serializer.serialize_struct(#class_name, #len)?;
+                #(state.serialize_field(#keys, &self #places)?;)*
+                state.end()
+            }
+        }
+    }
+}
+
+/// create <code>XXX::deserialize</code> which reads the same fields as <code>Serialize</code>,
+/// then builds the object and its prototypes from them directly and sets <code>\_\_real\_\_</code> like <code>new</code>,
+/// the error of validators is reported by <code>serde::de::Error::custom</code>.
+fn create_deserialize(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    let data = format_ident!("__{}Data__", name);
+    let class_name = name.to_string();
+    let fields = get_serde_fields(info);
+    let keys: Vec<&Ident> = fields.iter().map(|(key, _, _)| key).collect();
+
+    let mut classes = vec![info.clone()];
+    classes.append(&mut info.get_mro());
+    // fields with `#[default]` are optional in the input, and set to their default value if missing.
+    let defaults: Vec<Option<TokenStream>> = classes.iter().rev().flat_map(|x| x.get_fields()).map(|x| get_default(&x)).collect();
+    let data_fields = fields.iter().zip(&defaults).map(|((key, ty, _), default)| match default {
+        Some(_) => quote!{ #[serde(default)] #key: ::core::option::Option<#ty> },
+        None => quote!{ #key: #ty },
+    });
+    let values: Vec<TokenStream> = keys.iter().zip(&defaults).map(|(key, default)| match default {
+        Some(default) => quote!{ data.#key.unwrap_or_else(|| #default) },
+        None => quote!{ data.#key },
+    }).collect();
+    let null_real = info.get_null_real();
+    let _box = info.get_box();
+    let mut value = quote!{};
+    let mut offset = 0;
+    for (depth, class) in classes.iter().enumerate().rev() {
+        let ident = class.get_ident();
+        let prototype = if depth == classes.len() - 1 {
+            quote!{}
+        } else if class.is_inline() {
+            quote!{ __prototype__: #value, }
+        } else {
+            quote!{ __prototype__: #_box::pin(#value), }
+        };
+        let borrow = get_borrow_init(class);
+        let own: Vec<Ident> = class.get_fields().into_iter().map(|x| x.ident.unwrap()).collect();
+        let own_values = &values[offset..offset + own.len()];
+        offset += own.len();
+        value = quote!{
+            #ident {
+                #prototype
+                __real__: #null_real,
+                _pinned: ::core::marker::PhantomPinned,
+                #borrow
+                #(#own: #own_values),*
+            }
+        };
+    }
+
+    let pointer = info.get_pointer();
+    let create_this = create_pinned(info, value);
+    info.extra_items.push(syn::parse2(quote!{
+        #[derive(::serde::Deserialize)]
+        #[serde(rename = #class_name)]
+        struct #data {
+            #(#data_fields),*
+        }
+    }).unwrap());
+    let validate = find_method(info, "__validate__").map(|_| quote!{
+        if let ::core::result::Result::Err(err) = this.__validate__() {
+            return ::core::result::Result::Err(__E__::custom(::core::format_args!("failed to create {}: {:?}", #class_name, err)));
+        }
+    });
+    info._impl.as_mut().unwrap().items.push(syn::parse2(quote!{
+        #[doc(hidden)]
+        fn __from_data__<__E__: ::serde::de::Error>(data: #data) -> ::core::result::Result<#pointer, __E__> {
+            #create_this
+            #validate
+            ::core::result::Result::Ok(this)
+        }
+    }).unwrap());
+    info._impl.as_mut().unwrap().items.push(syn::parse2(quote!{
+        pub fn deserialize<'de, __D__: ::serde::Deserializer<'de>>(deserializer: __D__) -> ::core::result::Result<#pointer, __D__::Error> {
+            let data = <#data as ::serde::Deserialize>::deserialize(deserializer)?;
+            Self::__from_data__(data)
+        }
+    }).unwrap());
+}
+
+/// add <code>\_\_as\_any\_\_</code> to the methods of class whose root class has <code>#\[derive(Serialize)\]</code> for <code>tagged!</code>.
+pub fn create_as_any(info: &mut ClassInfo) {
+    let root = info.get_mro().pop().unwrap_or_else(|| info.clone());
+    if !cfg!(feature = "serde") || !has_derive(&root, "Serialize") {
+        return;
+    }
+    let mut as_any: syn::ImplItemMethod = syn::parse2(quote!{
+        #[doc(hidden)]
+        fn __as_any__(&self) -> &dyn ::core::any::Any {}
+    }).unwrap();
+    // the receiver is returned verbatim, `self` is not converted to the most derived object.
+    as_any.block.stmts.push(syn::Stmt::Expr(syn::Expr::Verbatim(quote!{ self })));
+    info.add_items([syn::ImplItem::Method(as_any)]);
+}
+
+/// classes of the hierarchy of <code>root</code> with <code>#\[derive(derive)\]</code>, sorted by name.
+#[cfg(feature = "serde")]
+fn get_tagged_classes(root: &ClassInfo, derive: &str) -> Vec<ClassInfo> {
+    let root_name = root.get_ident();
+    let mut classes: Vec<ClassInfo> = CLASSES.lock().unwrap().values()
+        .map(|x| ClassInfo::deserialize(x.to_string()))
+        .collect();
+    classes.retain(|x| has_derive(x, derive) && (x.get_ident() == root_name || x.get_mro().iter().any(|x| x.get_ident() == root_name)));
+    classes.sort_by_key(|x| x.get_ident().to_string());
+    classes
+}
+
+/// implement <code>Serialize</code> for <code>dyn \_\_Root\_\_</code> and create <code>Root::deserialize_dyn</code>,
+/// an object is tagged by the name of its class, like <code>{"Rectangle": {...}}</code>.
+#[cfg(feature = "serde")]
+pub fn create_tagged(root: Ident) -> TokenStream {
+    let info = match CLASSES.lock().unwrap().get(&root.to_string()) {
+        Some(x) => ClassInfo::deserialize(x.to_string()),
+        None => return syn::Error::new_spanned(&root, format!("{} is not a class", root)).to_compile_error(),
+    };
+    if info._parent.is_some() {
+        return syn::Error::new_spanned(&root, format!("`tagged!` should be used on the root class of {}", root)).to_compile_error();
+    }
+    let root_name = root.to_string();
+    let real = info.get_real();
+    let pointer = get_dyn_pointer(&info);
+    let alloc = info.get_alloc();
+    let mut items: Vec<TokenStream> = Vec::new();
+
+    if has_derive(&info, "Serialize") {
+        let classes: Vec<Ident> = get_tagged_classes(&info, "Serialize").iter().map(|x| x.get_ident()).collect();
+        let names: Vec<String> = classes.iter().map(|x| x.to_string()).collect();
+        let indexes: Vec<u32> = (0..classes.len() as u32).collect();
+        let err = format!("the class is not a subclass of {} with `#[derive(Serialize)]`", root_name);
+        items.push(quote!{
+            impl ::serde::Serialize for dyn #real {
+                fn serialize<__S__: ::serde::Serializer>(&self, serializer: __S__) -> ::core::result::Result<__S__::Ok, __S__::Error> {
+                    let any = self.__as_any__();
+                    #(
+                        if let ::core::option::Option::Some(x) = any.downcast_ref::<#classes>() {
+                            return serializer.serialize_newtype_variant(#root_name, #indexes, #names, x);
+                        }
+                    )*
+                    ::core::result::Result::Err(<__S__::Error as ::serde::ser::Error>::custom(#err))
+                }
+            }
+        });
+        items.push(quote!{
+            impl #root {
+                pub fn serialize_dyn<__S__: ::serde::Serializer>(value: &(dyn #real + 'static), serializer: __S__) -> ::core::result::Result<__S__::Ok, __S__::Error> {
+                    ::serde::Serialize::serialize(value, serializer)
+                }
+
+                pub fn serialize_dyn_vec<__S__: ::serde::Serializer>(values: &[#pointer], serializer: __S__) -> ::core::result::Result<__S__::Ok, __S__::Error> {
+                    serializer.collect_seq(values.iter().map(|x| &**x))
+                }
+            }
+        });
+    }
+
+    let classes: Vec<Ident> = get_tagged_classes(&info, "Deserialize").iter().map(|x| x.get_ident()).collect();
+    if !classes.is_empty() {
+        let data: Vec<Ident> = classes.iter().map(|x| format_ident!("__{}Data__", x)).collect();
+        items.push(quote!{
+            #[derive(::serde::Deserialize)]
+            #[serde(rename = #root_name)]
+            enum __Tagged__ {
+                #(#classes(#data)),*
+            }
+        });
+        items.push(quote!{
+            fn __from_tagged__<__E__: ::serde::de::Error>(tagged: __Tagged__) -> ::core::result::Result<#pointer, __E__> {
+                match tagged {
+                    #(__Tagged__::#classes(data) => {
+                        let this: #pointer = #classes::__from_data__(data)?;
+                        ::core::result::Result::Ok(this)
+                    }),*
+                }
+            }
+        });
+        items.push(quote!{
+            impl #root {
+                pub fn deserialize_dyn<'de, __D__: ::serde::Deserializer<'de>>(deserializer: __D__) -> ::core::result::Result<#pointer, __D__::Error> {
+                    let tagged = <__Tagged__ as ::serde::Deserialize>::deserialize(deserializer)?;
+                    __from_tagged__(tagged)
+                }
+
+                pub fn deserialize_dyn_vec<'de, __D__: ::serde::Deserializer<'de>>(deserializer: __D__) -> ::core::result::Result<#alloc::vec::Vec<#pointer>, __D__::Error> {
+                    let tagged = <#alloc::vec::Vec<__Tagged__> as ::serde::Deserialize>::deserialize(deserializer)?;
+                    tagged.into_iter().map(__from_tagged__).collect()
+                }
+            }
+        });
+    }
+
+    quote!{
+        const _: () = {
+            #(#items)*
+        };
+    }
+}
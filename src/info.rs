@@ -1,8 +1,8 @@
 use std::{collections::HashMap};
 
-use proc_macro2::Ident;
-use quote::{ToTokens};
-use syn::{self, Token, ItemStruct, ItemImpl, Result, ItemTrait};
+use proc_macro2::{Ident, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{self, Token, ItemStruct, ItemImpl, ImplItem, Result, ItemTrait, Expr, Item, Field, punctuated::Punctuated};
 
 use crate::CLASSES;
 mod kw {
@@ -57,12 +57,39 @@ impl Serializable for ParentInfo {
     }
 }
 
+/// arguments of <code>super_new!</code> in a constructor.
+/// 
+/// <code>super_new!(a, b)</code> calls <code>Parent::new(a, b)</code>,
+/// <code>super_new!(with; a)</code> calls <code>Parent::with(a)</code>.
+pub struct SuperNew {
+    pub constructor: Option<Ident>,
+    pub args: Punctuated<Expr, Token![,]>,
+}
+
+impl syn::parse::Parse for SuperNew {
+    fn parse(input: syn::parse::ParseStream) -> Result<Self> {
+        let constructor: Option<Ident> = if input.peek(syn::Ident) && input.peek2(Token![;]) {
+            let constructor = input.parse()?;
+            input.parse::<Token![;]>()?;
+            Some(constructor)
+        } else {
+            None
+        };
+        Ok(Self {
+            constructor,
+            args: Punctuated::parse_terminated(input)?
+        })
+    }
+}
+
 pub struct ClassInfo {
     pub _parent: Option<ParentInfo>,
     pub _struct: Option<ItemStruct>,
     pub _impl: Option<ItemImpl>,
     pub _trait_impl: HashMap<Box<syn::Ident>, Box<ItemImpl>>,
     pub real_trait: Option<ItemTrait>,
+    /// other items generated for this class, like builder.
+    pub extra_items: Vec<Item>,
 }
 
 impl ClassInfo {
@@ -74,6 +101,125 @@ impl ClassInfo {
         self._struct.as_ref().unwrap().ident.clone()
     }
 
+    /// fields declared by user, without <code>\_\_prototype\_\_</code>, <code>\_\_real\_\_</code>, <code>_pinned</code> and <code>\_\_borrow\_\_</code>.
+    pub fn get_fields(&self) -> Vec<Field> {
+        self._struct.as_ref().unwrap().fields.iter()
+            .filter(|x| !matches!(x.ident.as_ref().map(|x| x.to_string()).as_deref(), Some("__prototype__" | "__real__" | "_pinned" | "__borrow__")))
+            .cloned().collect()
+    }
+
+    /// add generated items to the impl of this class before it is parsed,
+    /// so methods with <code>self</code> among them are declared in the root trait and overridden by every subclass,
+    /// and calls through an ancestor reach the ones of the most derived class.
+    pub fn add_items(&mut self, items: impl IntoIterator<Item = ImplItem>) {
+        self._impl.as_mut().unwrap().items.extend(items);
+    }
+
+    pub fn has_attr(&self, attr: &str) -> bool {
+        self._struct.as_ref().unwrap().attrs.iter().any(|x| x.path.is_ident(attr))
+    }
+
+    /// whether <code>\_\_prototype\_\_</code> is stored inline instead of <code>Pin<Box<Parent>></code>,
+    /// which is always true for class with <code>#\[rc\]</code> or <code>#\[arc\]</code>.
+    pub fn is_inline(&self) -> bool {
+        self._parent.is_some() && (self.has_attr("inline_parent") || self.get_shared().is_some())
+    }
+
+    /// whether this class or any ancestor has <code>#\[attr\]</code>.
+    pub fn inherits_attr(&self, attr: &str) -> bool {
+        self.has_attr(attr) || self.get_mro().iter().any(|x| x.has_attr(attr))
+    }
+
+    /// <code>::alloc</code> if this class or any ancestor has <code>#\[no_std\]</code>, otherwise <code>::std</code>.
+    pub fn get_alloc(&self) -> TokenStream {
+        if self.inherits_attr("no_std") {
+            quote!{ ::alloc }
+        } else {
+            quote!{ ::std }
+        }
+    }
+
+    /// path of <code>Box</code> from <code>get_alloc</code>.
+    pub fn get_box(&self) -> TokenStream {
+        let alloc = self.get_alloc();
+        quote!{ #alloc::boxed::Box }
+    }
+
+    /// paths of <code>(Rc, Weak)</code> or <code>(Arc, Weak)</code> if this class or any ancestor has <code>#\[rc\]</code> or <code>#\[arc\]</code>.
+    pub fn get_shared(&self) -> Option<(TokenStream, TokenStream)> {
+        let alloc = self.get_alloc();
+        if self.inherits_attr("arc") {
+            Some((quote!{ #alloc::sync::Arc }, quote!{ #alloc::sync::Weak }))
+        } else if self.inherits_attr("rc") {
+            Some((quote!{ #alloc::rc::Rc }, quote!{ #alloc::rc::Weak }))
+        } else {
+            None
+        }
+    }
+
+    /// the type returned by <code>new</code>, <code>Pin<Box<Self>></code>, <code>Rc<Self></code> or <code>Arc<Self></code>.
+    pub fn get_pointer(&self) -> TokenStream {
+        match self.get_shared() {
+            Some((rc, _)) => quote!{ #rc<Self> },
+            None => {
+                let _box = self.get_box();
+                quote!{ ::core::pin::Pin<#_box<Self>> }
+            },
+        }
+    }
+
+    /// the value of <code>\_\_real\_\_</code> before the object is allocated.
+    pub fn get_null_real(&self) -> TokenStream {
+        match self.get_shared() {
+            Some((_, weak)) => quote!{ #weak::<Self>::new() },
+            None => quote!{ ::core::ptr::null_mut::<Self>() },
+        }
+    }
+
+    /// whether this class or any ancestor has <code>#\[static_dispatch\]</code>.
+    pub fn is_static(&self) -> bool {
+        self.inherits_attr("static_dispatch")
+    }
+
+    /// whether this class or any ancestor has <code>#\[thread_safe\]</code>.
+    pub fn is_thread_safe(&self) -> bool {
+        self.inherits_attr("thread_safe")
+    }
+
+    /// the guard type of borrows if the root class has <code>#\[track_borrows\]</code>.
+    pub fn get_tracker(&self) -> Option<Ident> {
+        let root = self.get_mro().pop().unwrap_or_else(|| self.clone());
+        if root.has_attr("track_borrows") {
+            Some(quote::format_ident!("__{}Borrow__", root.get_ident()))
+        } else {
+            None
+        }
+    }
+
+    /// <code>is_inline</code> of this class and its ancestors, the nearest first.
+    pub fn get_layouts(&self) -> Vec<bool> {
+        let mut layouts = vec![self.is_inline()];
+        layouts.extend(self.get_mro().iter().map(|x| x.is_inline()));
+        layouts
+    }
+
+    /// raw pointer to the prototype at <code>depth</code> from the raw pointer <code>base</code>, without creating any reference.
+    pub fn get_prototype_ptr(&self, depth: usize, base: TokenStream) -> TokenStream {
+        let layouts = self.get_layouts();
+        let mut result = base;
+        for (inline, ancestor) in layouts[..depth].iter().zip(self.get_mro()) {
+            let field = quote!{ ::core::ptr::addr_of_mut!((*#result).__prototype__) };
+            result = if *inline {
+                field
+            } else {
+                let ancestor = ancestor.get_ident();
+                let _box = self.get_box();
+                quote!{ ::core::ptr::addr_of_mut!(**(#field as *mut #_box<#ancestor>)) }
+            };
+        }
+        result
+    }
+
     pub fn get_parent_info(&self) -> ClassInfo {
         let class_map = CLASSES.lock().unwrap();
 
@@ -140,7 +286,8 @@ impl syn::parse::Parse for ClassInfo {
             _struct,
             _impl,
             _trait_impl,
-            real_trait: None
+            real_trait: None,
+            extra_items: Vec::new(),
         })
     }
 }
@@ -152,7 +299,8 @@ impl Clone for ClassInfo {
             _struct: self._struct.clone(), 
             _impl: self._impl.clone(), 
             _trait_impl: self._trait_impl.clone(), 
-            real_trait: self.real_trait.clone()
+            real_trait: self.real_trait.clone(),
+            extra_items: self.extra_items.clone(),
         }
     }
 }
@@ -175,6 +323,37 @@ impl Serializable for ClassInfo {
     }
 }
 
+/// go through <code>depth</code> prototypes from <code>base</code>, which is <code>&mut</code> or a place of a class.
+/// 
+/// the result is a place for inline prototype, or <code>&mut</code> for boxed prototype, and need <code>unsafe</code>.
+pub fn get_prototype_mut(layouts: &[bool], depth: usize, base: TokenStream) -> TokenStream {
+    let mut result = base;
+    for inline in &layouts[..depth] {
+        result = if *inline {
+            quote!{ #result .__prototype__ }
+        } else {
+            quote!{ #result .__prototype__.as_mut().get_unchecked_mut() }
+        };
+    }
+    result
+}
+
+/// convert <code>LandVehicle</code> to <code>land_vehicle</code>.
+pub fn to_snake_case(ident: &Ident) -> String {
+    let mut result = String::new();
+    for (i, c) in ident.to_string().chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
 pub struct SyncType(pub Ident);
 
 unsafe impl Sync for SyncType {}
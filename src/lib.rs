@@ -6,14 +6,18 @@
 
 use std::{collections::HashMap, sync::Mutex};
 
-use parse_class::parse_class;
+use parse_class::{parse_class, get_output_struct, get_output_impl, get_output_trait, get_output_trait_impls};
 use proc_macro::TokenStream;
 use lazy_static::lazy_static;
 use quote::quote;
 
+mod builder;
+mod derive;
 mod info;
 mod parse_class;
 mod parse_expr;
+mod reflect;
+mod registry;
 use info::ClassInfo;
 
 use crate::info::Serializable;
@@ -30,18 +34,22 @@ lazy_static!{
 /// 
 /// and macro will auto generate a <code>new</code> function which return <code>Pin<Box<Self>></code>.
 /// 
+/// for class with parent, <code>new</code> takes parameters of parent's <code>new</code> first, a parameter with the same name as a field will be prefixed by the class declaring it, like <code>vehicle_name</code>.
+/// 
 /// expression in the method will be converted.
 /// 
 /// instead of use <code>self</code>, using <code>this</code>.
 /// 
-/// <code>self</code> will be convert to use <code>unsafe { self.\_\_real\_\_.as_ref().unwrap() }</code>.
+/// <code>self</code> will be convert to use <code>unsafe { &*self.\_\_real\_\_ }</code> after checking it is not null, or a pointer from the receiver if it is the most derived object, so the receiver is not invalidated by <code>\_\_real\_\_</code>.
 /// 
-/// <code>self_mut</code> will be convert to use <code>unsafe { self.\_\_real\_\_.as_mut().unwrap() }</code>.
+/// <code>self_mut</code> will be convert to use <code>unsafe { &mut *self.\_\_real\_\_ }</code> in the same way, using it in a method with <code>&self</code> is still an aliasing violation, see <code>#\[track_borrows\]</code>.
 /// 
 /// <code>\_super</code> will be convert to use <code>self.\_\_prototype\_\_</code>.
 /// 
 /// <code>\_super_mut</code> will be convert to use <code>unsafe { self.\_\_prototype\_\_.as_mut().get_unchecked_mut() }</code>.
 /// 
+/// <code>\_super::<Ancestor></code> and <code>\_super_mut::<Ancestor></code> will go through <code>\_\_prototype\_\_</code> until reaching <code>Ancestor</code>, which must be one of the ancestors.
+/// 
 /// 
 /// An example to use this macro:
 /// ```rust
@@ -87,6 +95,305 @@ lazy_static!{
 /// }
 /// ```
 /// 
+/// attribute <code>#\[keep\]</code>
+/// 
+/// methods with <code>#\[keep\]</code> stay in the impl of the class instead of <code>\_\_XXX\_\_</code>.
+/// they are inherited by subclasses through forwarding methods calling the ones of the parent, unless the subclass declares a method with the same name.
+/// methods using <code>Self</code> in the signature are not inherited, they are named in the doc of the subclass, which can declare its own one.
+/// ```ignore
+/// class!{
+///     struct Vehicle { }
+///     impl Vehicle {
+///         #[keep]
+///         fn wheels(&self) -> u32 { 4 }
+///     }
+/// }
+/// // `Car` extends `Vehicle`
+/// assert_eq!(Car::new().wheels(), 4);
+/// ```
+/// 
+/// attribute <code>#\[default\]</code>
+/// 
+/// using <code>#\[default\]</code> or <code>#\[default = value\]</code> for field make that field not a parameter of <code>new</code>.
+/// it will be <code>Default::default()</code> or <code>value</code> instead.
+/// ```ignore
+/// struct Vehicle {
+///     name: String,
+///     #[default = 4]
+///     wheels: u32
+/// }
+/// 
+/// let vehicle = Vehicle::new(String::from("car"));
+/// ```
+/// 
+/// attribute <code>#\[pin\]</code>
+/// 
+/// using <code>#\[pin\]</code> for field make it structurally pinned, so futures or other <code>!Unpin</code> values can be stored as fields.
+/// <code>xxx_pin(self: Pin<&mut Self>)</code> will return <code>Pin<&mut T></code> for it.
+/// <code>xxx_mut(self: Pin<&mut Self>)</code> will return <code>&mut T</code> for other fields of the class.
+/// the class is never <code>Unpin</code> and implementing <code>Unpin</code> for it is an error, <code>Drop</code> of it should not move fields with <code>#\[pin\]</code>.
+/// ```ignore
+/// struct Task {
+///     name: String,
+///     #[pin]
+///     countdown: Countdown
+/// }
+/// 
+/// task.as_mut().countdown_pin().poll(&mut cx);
+/// task.as_mut().name_mut().push_str("-finished");
+/// ```
+/// 
+/// attribute <code>#\[inline_parent\]</code>
+/// 
+/// using <code>#\[inline_parent\]</code> for struct with parent make <code>\_\_prototype\_\_</code> be the parent itself instead of <code>Pin<Box<Parent>></code>.
+/// the object and its prototypes are in one allocation.
+/// ```ignore
+/// extends Shape;
+/// #[inline_parent]
+/// struct Square { }
+/// ```
+/// 
+/// attribute <code>#\[deref\]</code>
+/// 
+/// using <code>#\[deref\]</code> for struct make it and its subclasses implement <code>Deref<Target = Parent></code>, so fields and methods with <code>#\[keep\]</code> of ancestors can be used from outside the class.
+/// <code>parent_pin(self: Pin<&mut Self>)</code> will return <code>Pin<&mut Parent></code> for class which is not shared.
+/// <code>DerefMut</code> is not implemented because the parent should not be moved.
+/// ```ignore
+/// extends Rectangle;
+/// #[deref]
+/// struct Square { }
+/// 
+/// assert_eq!(square.width, 2.0);
+/// let rectangle: Pin<&mut Rectangle> = square.as_mut().parent_pin();
+/// ```
+/// 
+/// attribute <code>#\[no_std\]</code>
+/// 
+/// using <code>#\[no_std\]</code> for the root class make it and its subclasses use <code>::alloc</code> instead of <code>::std</code> for <code>Box</code>, <code>Rc</code> and <code>Arc</code>.
+/// the crate should have <code>extern crate alloc;</code>, other generated paths always use <code>::core</code>.
+/// ```ignore
+/// extern crate alloc;
+/// 
+/// class!{
+///     #[no_std]
+///     struct Device {
+///         pin: u8
+///     }
+///     impl Device { }
+/// }
+/// ```
+/// 
+/// attribute <code>#\[static_dispatch\]</code>
+/// 
+/// using <code>#\[static_dispatch\]</code> for struct make methods called on a known most derived object use <code>Self</code> instead of <code>dyn</code> through <code>\_\_real\_\_</code>, so they can be inlined.
+/// subclasses of it use static dispatch too.
+/// ```ignore
+/// #[static_dispatch]
+/// struct Shape { }
+/// ```
+/// 
+/// attribute <code>#\[rc\]</code> and <code>#\[arc\]</code>
+/// 
+/// using <code>#\[rc\]</code> or <code>#\[arc\]</code> for the root class make <code>new</code> of it and its subclasses return <code>Rc<Self></code> or <code>Arc<Self></code>.
+/// <code>\_\_real\_\_</code> will be a <code>Weak</code> to the most derived object, which can be got by <code>this.this_rc()</code> and <code>this.this_weak()</code>.
+/// a method using <code>self</code> upgrades it once at the start, and panic if the object is being dropped.
+/// <code>self_mut</code> is not available, so use interior mutability instead.
+/// ```ignore
+/// #[rc]
+/// struct Widget {
+///     children: RefCell<Vec<Rc<dyn __Widget__>>>
+/// }
+/// 
+/// let button: Rc<Button> = Button::new(String::from("ok"));
+/// panel.add(button.this_rc());
+/// ```
+/// 
+/// attribute <code>#\[thread_safe\]</code>
+/// 
+/// using <code>#\[thread_safe\]</code> for the root class make <code>Send + Sync</code> supertraits of <code>\_\_XXX\_\_</code> of it and its subclasses.
+/// <code>Send</code> and <code>Sync</code> are implemented for them if all their fields and ancestors are <code>Send</code> and <code>Sync</code>, otherwise it will fail to compile.
+/// <code>self_mut</code> can not be used in methods with <code>&self</code> of them, because other threads may share the object.
+/// ```ignore
+/// #[arc]
+/// #[thread_safe]
+/// struct Counter {
+///     count: Mutex<u32>
+/// }
+/// ```
+/// 
+/// attribute <code>#\[track_borrows\]</code>
+/// 
+/// using <code>#\[track_borrows\]</code> for the root class make <code>self</code> and <code>self_mut</code> of it and its subclasses tracked like <code>RefCell</code> in debug builds.
+/// it will panic with names of methods if <code>self_mut</code> is used while a method with <code>&self</code> is running, or <code>self</code> is used while <code>self_mut</code> is borrowed.
+/// release builds will not check it.
+/// ```ignore
+/// #[track_borrows]
+/// struct Shape { }
+/// 
+/// // panics: Shape::reset can not borrow `self_mut` while it is borrowed by Shape::reset
+/// rectangle.reset();
+/// ```
+/// 
+/// attribute <code>#\[meta\]</code>
+/// 
+/// using <code>#\[meta\]</code> for the root class make it and its subclasses have <code>META</code>, a <code>ClassMeta</code> defined by <code>def_class_meta</code>.
+/// it has names and types of fields and signatures of methods declared or overridden by the class.
+/// <code>class_meta()</code> in <code>\_\_XXX\_\_</code> return <code>META</code> of the most derived class.
+/// ```ignore
+/// #[meta]
+/// struct Shape {
+///     color: String
+/// }
+/// 
+/// assert_eq!(shape.class_meta().name, "Rectangle");
+/// ```
+/// 
+/// attribute <code>#\[reflect\]</code>
+/// 
+/// using <code>#\[reflect\]</code> for method or field of class with <code>#\[meta\]</code> make it available by <code>invoke(name, &[Value])</code>, <code>get_field(name)</code> and <code>set_field(name, Value)</code> in <code>\_\_XXX\_\_</code>.
+/// arguments, return values and fields should be <code>()</code>, <code>bool</code>, integers, floats or <code>String</code>.
+/// <code>ReflectError</code> is returned for an unknown member, wrong arity or type mismatch.
+/// ```ignore
+/// impl Shape {
+///     #[reflect]
+///     fn set_width(&mut self, width: f32) { this.width = width; }
+/// }
+/// 
+/// shape.invoke("set_width", &[Value::F32(3.0)]);
+/// assert_eq!(shape.get_field("width"), Ok(Value::F32(3.0)));
+/// ```
+/// 
+/// attribute <code>#\[register\]</code>
+/// 
+/// using <code>#\[register\]</code> for the root class register it and its non-generic subclasses to <code>registry</code> defined by <code>def_registry</code> when the program starts.
+/// <code>registry</code>, <code>Value</code> and <code>ReflectError</code> should be in scope of the classes.
+/// classes are registered by a function in <code>.init_array</code> on ELF targets, <code>\_\_mod\_init\_func</code> on Apple targets and <code>.CRT$XCU</code> on Windows, other targets register nothing.
+/// ```ignore
+/// #[register]
+/// struct Shape { }
+/// 
+/// let shape = registry::create::<dyn __Shape__>("Square", &[Value::F32(2.0)]).unwrap();
+/// ```
+/// 
+/// attribute <code>#\[builder\]</code>
+/// 
+/// using <code>#\[builder\]</code> for struct will generate <code>XXX::builder()</code>, which has setters for parameters of <code>new</code> and fields with <code>#\[default\]</code> in the hierarchy.
+/// <code>build()</code> will return <code>Err</code> if a parameter of <code>new</code> is missing.
+/// if the class has validators, they run after all fields are set and <code>build()</code> returns <code>XXXBuildError::Missing</code> or <code>XXXBuildError::Invalid</code>.
+/// ```ignore
+/// #[builder]
+/// pub struct Car {
+///     name: String
+/// }
+/// 
+/// let car = Car::builder().name(String::from("car1")).wheels(3).build().unwrap();
+/// ```
+/// 
+/// attribute <code>#\[derive(Debug, PartialEq, Eq, Hash)\]</code>
+/// 
+/// using them for struct will implement them with user fields of the class and all its ancestors.
+/// <code>\_\_real\_\_</code>, <code>\_\_prototype\_\_</code> and other internal fields are skipped, other derives are passed through.
+/// ```ignore
+/// #[derive(Debug, PartialEq)]
+/// struct Point {
+///     x: i32
+/// }
+/// 
+/// assert_eq!(*Point::new(1), *Point::new(1));
+/// ```
+/// 
+/// attribute <code>#\[derive(Clone)\]</code>
+/// 
+/// using <code>#\[derive(Clone)\]</code> for struct will generate <code>clone_object(&self)</code>, which deep clones all prototypes into a new object with <code>\_\_real\_\_</code> set like <code>new</code>.
+/// <code>clone_dyn(&self)</code> in the root trait return <code>Pin<Box<dyn \_\_Root\_\_>></code> of the most derived type.
+/// subclasses of it should derive <code>Clone</code> too.
+/// ```ignore
+/// #[derive(Clone)]
+/// struct Shape { }
+/// 
+/// let copy: Pin<Box<dyn __Shape__>> = shape.clone_dyn();
+/// ```
+/// 
+/// attribute <code>#\[derive(Serialize, Deserialize)\]</code>
+/// 
+/// with feature <code>serde</code>, using them for struct will implement <code>Serialize</code> as a struct with user fields of the whole hierarchy from the root class.
+/// a field with the same name as a field of its subclass is prefixed like <code>vehicle_name</code>.
+/// <code>XXX::deserialize(deserializer)</code> return <code>Pin<Box<Self>></code> with <code>\_\_real\_\_</code> set and validators run.
+/// fields with <code>#\[default\]</code> may be missing in the input, and are set to their default value.
+/// the crate should depend on <code>serde</code> with feature <code>derive</code>.
+/// ```ignore
+/// #[derive(Serialize, Deserialize)]
+/// struct Point {
+///     x: i32,
+///     y: i32
+/// }
+/// 
+/// let point = Point::deserialize(&mut serde_json::Deserializer::from_str(r#"{"x":1,"y":2}"#)).unwrap();
+/// ```
+/// 
+/// attribute <code>#\[validate\]</code>
+/// 
+/// using <code>#\[validate\]</code> for method returning <code>Result<(), E></code> will generate <code>try_new</code> which return <code>Result<Pin<Box<Self>>, E></code>.
+/// validators run from the root class to this class before the object is allocated, and <code>new</code> will panic if validation failed.
+/// constructors with <code>#\[constructor\]</code> and <code>build()</code> of <code>#\[builder\]</code> validate in the same way, class with <code>#\[rc\]</code> or <code>#\[arc\]</code> is validated after it is created.
+/// ```ignore
+/// impl Shape {
+///     #[validate]
+///     fn check(&self) -> Result<(), String> {
+///         if this.scale <= 0.0 {
+///             return Err(String::from("scale should be positive"));
+///         }
+///         Ok(())
+///     }
+/// }
+/// 
+/// assert!(Shape::try_new(-1.0).is_err());
+/// ```
+/// 
+/// attribute <code>#\[constructor\]</code>
+/// 
+/// using <code>#\[constructor\]</code> for method make it a constructor which return <code>Pin<Box<Self>></code>, <code>new</code> will not be generated if there is a constructor named <code>new</code>.
+/// a constructor should end with <code>Self { ... }</code> for its own fields.
+/// class with parent should call <code>super_new!(args)</code> for <code>Parent::new(args)</code> or <code>super_new!(with; args)</code> for <code>Parent::with(args)</code> before that.
+/// if the class has validators, <code>try_xxx</code> is generated for constructor <code>xxx</code> and the constructor panics if validation failed.
+/// ```ignore
+/// impl Square {
+///     #[constructor]
+///     fn new(size: f32) {
+///         super_new!(size, size);
+///         Self { }
+///     }
+/// }
+/// ```
+/// 
+/// names of classes
+/// 
+/// every class has <code>CLASS_NAME</code>, <code>PARENT_CLASS_NAME</code> and <code>MRO</code>, which is names of the class and its ancestors from the class to the root class.
+/// <code>class_name()</code>, <code>parent_class_name()</code> and <code>mro()</code> in <code>\_\_XXX\_\_</code> return them of the most derived class.
+/// a member whose name is already declared by the class is not generated, and a method declared by an ancestor is not overridden.
+/// these names are reserved, a method with one of them in another trait implemented by the class makes calls on the class ambiguous.
+/// ```ignore
+/// assert_eq!(Car::MRO, &["Car", "LandVehicle", "Vehicle"]);
+/// let vehicle: &dyn __Vehicle__ = &*car;
+/// assert_eq!(vehicle.class_name(), "Car");
+/// ```
+/// 
+/// attribute <code>#\[class_method\]</code>
+/// 
+/// using <code>#\[class_method\]</code> for a method without <code>self</code> make it a class method.
+/// a class which has or inherits class methods has metaclass <code>XXXClass</code> implementing <code>\_\_XXXClass\_\_</code> with the class methods and <code>class_name()</code>, which is returned by <code>XXX::class()</code>.
+/// <code>class()</code> in <code>\_\_XXX\_\_</code> return the metaclass of the most derived class as <code>&dyn \_\_RootClass\_\_</code> of the farthest ancestor with class methods.
+/// a class method with the same name overrides the one of the parent, its signature should be the same and not use <code>Self</code>.
+/// ```ignore
+/// impl Shape {
+///     #[class_method]
+///     fn display_name() -> String { String::from("shape") }
+/// }
+/// 
+/// assert_eq!(Square::class().display_name(), "square");
+/// assert_eq!(shape.class().display_name(), "square");
+/// ```
+///
 
 #[proc_macro]
 pub fn class(token: TokenStream) -> TokenStream {
@@ -99,15 +406,16 @@ pub fn class(token: TokenStream) -> TokenStream {
 
     parse_class(class_info);
 
-    let _struct = class_info._struct.as_ref().unwrap();
+    let _struct = get_output_struct(class_info);
     if class_info._impl.is_none() {
         panic!("there is no impl for this struct");
     }
-    let _impl = class_info._impl.as_ref().unwrap();
-    let _trait_impl = class_info._trait_impl.values();
+    let _impl = get_output_impl(class_info);
+    let _trait_impl = get_output_trait_impls(class_info);
 
     let name = class_info.get_ident();
-    let _trait = class_info.real_trait.as_ref().unwrap();
+    let _trait = get_output_trait(class_info);
+    let extra_items = &class_info.extra_items;
     CLASSES.lock().as_mut().unwrap().insert(name.to_string(), class_info.serialize());
     
     quote!{
@@ -115,6 +423,7 @@ pub fn class(token: TokenStream) -> TokenStream {
         #_struct
         #_impl
         #(#_trait_impl)*
+        #(#extra_items)*
     }.into()
 }
 
@@ -142,4 +451,235 @@ pub fn def_as_mut(_: TokenStream) -> TokenStream {
             };
         }
     }.into()
-}
\ No newline at end of file
+}
+
+/// <code>::alloc</code> for <code>no\_std</code>, or <code>::std</code> for nothing.
+fn parse_alloc(token: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    match syn::parse::<Option<syn::Ident>>(token)? {
+        None => Ok(quote!{ ::std }),
+        Some(x) if x == "no_std" => Ok(quote!{ ::alloc }),
+        Some(x) => Err(syn::Error::new_spanned(&x, "expected `no_std` or nothing")),
+    }
+}
+
+/// this macro will define <code>ClassMeta</code>, <code>FieldMeta</code> and <code>MethodMeta</code> for class with <code>#\[meta\]</code>,
+/// and <code>Value</code>, <code>ReflectValue</code> and <code>ReflectError</code> for members with <code>#\[reflect\]</code>.
+/// 
+/// <code>def_class_meta!(no_std)</code> takes <code>Vec</code> and <code>String</code> from <code>alloc</code> instead of <code>std</code>,
+/// the crate should declare <code>extern crate alloc</code>.
+/// 
+/// example:
+/// ```ignore
+/// def_class_meta!();
+/// 
+/// fn main() {
+///     let car = Car::new(String::from("Car"), String::from("car1"));
+///     let fields: Vec<&str> = car.class_meta().all_fields().iter().map(|x| x.name).collect();
+///     assert_eq!(fields, vec!["_type", "name"]);
+/// }
+/// ```
+#[proc_macro]
+pub fn def_class_meta(token: TokenStream) -> TokenStream {
+    let alloc = match parse_alloc(token) {
+        Ok(alloc) => alloc,
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let values = [
+        ("Bool", quote!{ bool }, "bool"), ("I32", quote!{ i32 }, "i32"), ("I64", quote!{ i64 }, "i64"),
+        ("U32", quote!{ u32 }, "u32"), ("U64", quote!{ u64 }, "u64"), ("F32", quote!{ f32 }, "f32"), ("F64", quote!{ f64 }, "f64"),
+        ("String", quote!{ #alloc::string::String }, "String"),
+    ];
+    let variants: Vec<syn::Ident> = values.iter().map(|(x, _, _)| syn::Ident::new(x, proc_macro2::Span::call_site())).collect();
+    let types: Vec<&proc_macro2::TokenStream> = values.iter().map(|(_, x, _)| x).collect();
+    let type_names: Vec<&str> = values.iter().map(|(_, _, x)| *x).collect();
+    quote!{
+        /// a field of a class.
+        #[derive(Debug)]
+        pub struct FieldMeta {
+            pub name: &'static str,
+            pub type_name: &'static str,
+            /// the class declaring the field.
+            pub class: &'static str,
+        }
+
+        /// a method of a class.
+        #[derive(Debug)]
+        pub struct MethodMeta {
+            pub name: &'static str,
+            pub signature: &'static str,
+            /// the class declaring or overriding the method.
+            pub class: &'static str,
+        }
+
+        /// fields and methods declared or overridden by a class.
+        #[derive(Debug)]
+        pub struct ClassMeta {
+            pub name: &'static str,
+            pub parent: ::core::option::Option<&'static ClassMeta>,
+            pub fields: &'static [FieldMeta],
+            pub methods: &'static [MethodMeta],
+        }
+
+        impl ClassMeta {
+            /// this class and its ancestors, from this class to the root class.
+            pub fn mro(&'static self) -> #alloc::vec::Vec<&'static ClassMeta> {
+                let mut result = #alloc::vec::Vec::new();
+                let mut current = ::core::option::Option::Some(self);
+                while let ::core::option::Option::Some(meta) = current {
+                    result.push(meta);
+                    current = meta.parent;
+                }
+                result
+            }
+
+            /// fields of this class and its ancestors, from the root class to this class.
+            pub fn all_fields(&'static self) -> #alloc::vec::Vec<&'static FieldMeta> {
+                self.mro().iter().rev().flat_map(|x| x.fields.iter()).collect()
+            }
+
+            /// methods of this class and its ancestors, an overridden method is listed once with the class overriding it.
+            pub fn all_methods(&'static self) -> #alloc::vec::Vec<&'static MethodMeta> {
+                let mut result: #alloc::vec::Vec<&'static MethodMeta> = #alloc::vec::Vec::new();
+                for meta in self.mro().iter().rev() {
+                    for method in meta.methods {
+                        match result.iter_mut().find(|x| x.name == method.name) {
+                            ::core::option::Option::Some(x) => *x = method,
+                            ::core::option::Option::None => result.push(method),
+                        }
+                    }
+                }
+                result
+            }
+
+            /// the field named <code>name</code> of this class or its ancestors.
+            pub fn field(&'static self, name: &str) -> ::core::option::Option<&'static FieldMeta> {
+                self.mro().into_iter().find_map(|x| x.fields.iter().find(|x| x.name == name))
+            }
+
+            /// the method named <code>name</code> of this class or its ancestors.
+            pub fn method(&'static self, name: &str) -> ::core::option::Option<&'static MethodMeta> {
+                self.mro().into_iter().find_map(|x| x.methods.iter().find(|x| x.name == name))
+            }
+        }
+
+        /// a value passed to or returned from members with <code>#\[reflect\]</code>.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Value {
+            Unit,
+            #(#variants(#types),)*
+        }
+
+        /// a type which can be converted from and to <code>Value</code>.
+        pub trait ReflectValue: Sized {
+            const TYPE_NAME: &'static str;
+            fn from_value(value: &Value) -> ::core::option::Option<Self>;
+            fn into_value(self) -> Value;
+        }
+
+        impl ReflectValue for () {
+            const TYPE_NAME: &'static str = "()";
+            fn from_value(value: &Value) -> ::core::option::Option<Self> {
+                match value {
+                    Value::Unit => ::core::option::Option::Some(()),
+                    _ => ::core::option::Option::None,
+                }
+            }
+            fn into_value(self) -> Value {
+                Value::Unit
+            }
+        }
+
+        #(
+            impl ReflectValue for #types {
+                const TYPE_NAME: &'static str = #type_names;
+                fn from_value(value: &Value) -> ::core::option::Option<Self> {
+                    match value {
+                        Value::#variants(x) => ::core::option::Option::Some(::core::clone::Clone::clone(x)),
+                        _ => ::core::option::Option::None,
+                    }
+                }
+                fn into_value(self) -> Value {
+                    Value::#variants(self)
+                }
+            }
+        )*
+
+        /// the error of <code>invoke</code>, <code>get_field</code>, <code>set_field</code> and <code>registry::create</code>.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum ReflectError {
+            /// there is no method or field with <code>#\[reflect\]</code> of the name.
+            UnknownMember(#alloc::string::String),
+            WrongArity { name: &'static str, expected: usize, found: usize },
+            /// the argument at <code>index</code> is not <code>expected</code>.
+            TypeMismatch { name: &'static str, index: usize, expected: &'static str },
+            /// the object created by the registry is rejected by its validators.
+            Invalid { name: &'static str, error: #alloc::string::String },
+            /// the registered class <code>name</code> is not <code>base</code> or its subclass.
+            NotASubclass { name: #alloc::string::String, base: &'static str },
+        }
+
+        impl ::core::fmt::Display for ReflectError {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    ReflectError::UnknownMember(name) => write!(f, "unknown member `{}`", name),
+                    ReflectError::WrongArity { name, expected, found } => write!(f, "`{}` takes {} arguments but {} were given", name, expected, found),
+                    ReflectError::TypeMismatch { name, index, expected } => write!(f, "argument {} of `{}` should be {}", index, name, expected),
+                    ReflectError::Invalid { name, error } => write!(f, "failed to create {}: {}", name, error),
+                    ReflectError::NotASubclass { name, base } => write!(f, "{} is not a subclass of {}", name, base),
+                }
+            }
+        }
+    }.into()
+}
+
+/// this macro will define module <code>registry</code>, classes whose root class has <code>#\[register\]</code> are registered to it when the program starts.
+/// 
+/// <code>registry::classes()</code> return <code>ClassEntry</code> of all registered classes with their <code>META</code> if the root class has <code>#\[meta\]</code>, <code>registry::subclasses_of(name)</code> return the subclasses of a class,
+/// and <code>registry::create::<dyn \_\_Base\_\_>(name, &[Value])</code> create the class or its subclass by <code>new</code>, or <code>try_new</code> with <code>ReflectError::Invalid</code> if validators fail.
+/// a class can be created only if all parameters of <code>new</code> are types of <code>Value</code>, and a class which is not a subclass of <code>Base</code> returns <code>ReflectError::NotASubclass</code>.
+/// 
+/// <code>def_registry!(no_std)</code> takes <code>Vec</code> and <code>String</code> from <code>alloc</code> instead of <code>std</code>.
+/// 
+/// example:
+/// ```ignore
+/// def_class_meta!();
+/// def_registry!();
+/// 
+/// fn main() {
+///     let shape = registry::create::<dyn __Shape__>("Square", &[Value::F32(2.0)]).unwrap();
+///     assert_eq!(shape.cal_size(), 4.0);
+/// }
+/// ```
+#[proc_macro]
+pub fn def_registry(token: TokenStream) -> TokenStream {
+    let alloc = match parse_alloc(token) {
+        Ok(alloc) => alloc,
+        Err(err) => return err.into_compile_error().into(),
+    };
+    registry::create_registry(alloc).into()
+}
+
+/// this macro will implement <code>Serialize</code> for <code>dyn \_\_Root\_\_</code> and generate <code>Root::deserialize_dyn</code> with feature <code>serde</code>.
+/// 
+/// it should be used after all subclasses, objects are tagged by the name of their class like <code>{"Rectangle": {...}}</code>,
+/// and <code>serialize_dyn_vec</code> and <code>deserialize_dyn_vec</code> can be used by <code>#\[serde(serialize_with, deserialize_with)\]</code> for <code>Vec<Pin<Box<dyn \_\_Root\_\_>>></code>.
+/// 
+/// example:
+/// ```ignore
+/// tagged!(Shape);
+/// 
+/// fn main() {
+///     let json = r#"{"Rectangle": {"color": "red", "width": 2.0, "height": 3.0}}"#;
+///     let shape = Shape::deserialize_dyn(&mut serde_json::Deserializer::from_str(json)).unwrap();
+///     assert_eq!(shape.cal_size(), 6.0);
+/// }
+/// ```
+#[cfg(feature = "serde")]
+#[proc_macro]
+pub fn tagged(token: TokenStream) -> TokenStream {
+    let root: syn::Ident = match syn::parse(token) {
+        Ok(root) => root,
+        Err(err) => return err.into_compile_error().into(),
+    };
+    derive::create_tagged(root).into()
+}
@@ -1,8 +1,8 @@
 use proc_macro2::Ident;
-use quote::{ToTokens, quote};
-use syn::{self, ItemImpl, parse::Parser, Field, Pat, ImplItemMethod, TraitItem, ImplItem, FnArg, FieldValue};
+use quote::{ToTokens, quote, format_ident};
+use syn::{self, ItemImpl, ItemStruct, ItemTrait, parse::Parser, Field, Pat, ImplItemMethod, TraitItem, ImplItem, FnArg, FieldValue, Stmt, Expr, Item, Macro};
 
-use crate::{info::ClassInfo, parse_expr};
+use crate::{info::{ClassInfo, SuperNew, to_snake_case, get_prototype_mut}, parse_expr::{self, ExprContext}, builder, derive, reflect, registry};
 
 fn get_methods(item_impl: &ItemImpl) -> Vec<syn::ImplItemMethod> {
     item_impl.items.iter().filter_map(|item| match item {
@@ -36,7 +36,78 @@ fn clear_methods(item_impl: &mut ItemImpl) {
     })
 }
 
-fn create_new(info: &mut ClassInfo) {
+/// attributes of fields which are only for this macro.
+const FIELD_ATTRS: [&str; 3] = ["default", "pin", "reflect"];
+/// attributes of struct which are only for this macro.
+const STRUCT_ATTRS: [&str; 11] = ["builder", "inline_parent", "static_dispatch", "rc", "arc", "thread_safe", "track_borrows", "no_std", "meta", "deref", "register"];
+
+/// attributes of methods which are only for this macro.
+const METHOD_ATTRS: [&str; 3] = ["class_method", "keep", reflect::GENERATED];
+
+/// the value of a field with <code>#\[default\]</code> or <code>#\[default = value\]</code>.
+pub fn get_default(field: &Field) -> Option<proc_macro2::TokenStream> {
+    let attr = field.attrs.iter().find(|x| x.path.is_ident("default"))?;
+    if attr.tokens.is_empty() {
+        return Some(quote!{ ::core::default::Default::default() });
+    }
+    let parser = |input: syn::parse::ParseStream| {
+        input.parse::<syn::Token![=]>()?;
+        input.parse::<Expr>()
+    };
+    match parser.parse2(attr.tokens.clone()) {
+        Ok(expr) => Some(expr.to_token_stream()),
+        Err(err) => Some(err.to_compile_error()),
+    }
+}
+
+/// the struct to output, without attributes only for this macro.
+pub fn get_output_struct(info: &ClassInfo) -> ItemStruct {
+    let mut _struct = info._struct.clone().unwrap();
+    _struct.attrs.retain(|x| !STRUCT_ATTRS.iter().any(|attr| x.path.is_ident(attr)));
+    derive::strip_derives(&mut _struct);
+    for field in &mut _struct.fields {
+        field.attrs.retain(|x| !FIELD_ATTRS.iter().any(|attr| x.path.is_ident(attr)));
+    }
+    _struct
+}
+
+fn strip_method_attrs(_impl: &mut ItemImpl) {
+    for item in &mut _impl.items {
+        if let ImplItem::Method(method) = item {
+            method.attrs.retain(|x| !METHOD_ATTRS.iter().any(|attr| x.path.is_ident(attr)));
+        }
+    }
+}
+
+/// the impl to output, without attributes of methods only for this macro.
+pub fn get_output_impl(info: &ClassInfo) -> ItemImpl {
+    let mut _impl = info._impl.clone().unwrap();
+    strip_method_attrs(&mut _impl);
+    _impl
+}
+
+/// implementations of traits to output, without attributes of methods only for this macro.
+pub fn get_output_trait_impls(info: &ClassInfo) -> Vec<ItemImpl> {
+    info._trait_impl.values().map(|x| {
+        let mut _impl = x.as_ref().clone();
+        strip_method_attrs(&mut _impl);
+        _impl
+    }).collect()
+}
+
+/// trait <code>\_\_XXX\_\_</code> to output, without attributes of methods only for this macro.
+pub fn get_output_trait(info: &ClassInfo) -> ItemTrait {
+    let mut _trait = info.real_trait.clone().unwrap();
+    for item in &mut _trait.items {
+        if let TraitItem::Method(method) = item {
+            method.attrs.retain(|x| !METHOD_ATTRS.iter().any(|attr| x.path.is_ident(attr)));
+        }
+    }
+    _trait
+}
+
+/// parameters of <code>new</code> and values of fields, fields with <code>#\[default\]</code> are not parameters.
+fn get_new_fields(info: &ClassInfo) -> (Vec<FnArg>, Vec<FieldValue>) {
     let _struct = info._struct.as_ref().unwrap();
     let mut inputs: Vec<FnArg> = Vec::new();
     let mut fields: Vec<FieldValue> = Vec::new();
@@ -44,6 +115,10 @@ fn create_new(info: &mut ClassInfo) {
         if field.ident.as_ref().is_some() {
             let id = field.ident.as_ref().unwrap().clone();
             let ty = field.ty.clone();
+            if let Some(default) = get_default(field) {
+                fields.push(syn::parse2(quote!{#id: #default}).unwrap());
+                continue;
+            }
             
             inputs.push(syn::parse2(quote!{#id: #ty}).unwrap());
             fields.push(syn::parse2(quote!{#id: #id}).unwrap());
@@ -51,40 +126,257 @@ fn create_new(info: &mut ClassInfo) {
             panic!("not implement for unnamed field!");
         }
     }
+    (inputs, fields)
+}
+
+/// the constructor which return <code>Self</code> without pinned and <code>\_\_real\_\_</code> set,
+/// used by children with <code>#\[inline_parent\]</code>.
+/// initialize <code>\_\_borrow\_\_</code> of the root class with <code>#\[track_borrows\]</code>.
+pub fn get_borrow_init(info: &ClassInfo) -> proc_macro2::TokenStream {
+    if info._parent.is_none() && info.get_tracker().is_some() {
+        quote!{ __borrow__: ::core::default::Default::default(), }
+    } else {
+        quote!{}
+    }
+}
+
+fn get_unpinned(constructor: &Ident) -> Ident {
+    format_ident!("__{}_unpinned__", constructor)
+}
+
+/// the safety contract of unpinned constructors.
+pub fn get_unpinned_doc() -> proc_macro2::TokenStream {
+    quote!{
+        /// # Safety
+        /// 
+        /// <code>\_\_real\_\_</code> of the result is null, it should be pinned and have <code>\_\_real\_\_</code> set
+        /// before any method is called or any reference to it is given to other code.
+    }
+}
+
+fn create_new(info: &mut ClassInfo, validate_error: Option<proc_macro2::TokenStream>) {
+    let (inputs, fields) = get_new_fields(info);
+    let unpinned_doc = get_unpinned_doc();
+    let null_real = info.get_null_real();
+    let pointer = info.get_pointer();
+    let borrow = get_borrow_init(info);
+    let inputs_call = get_inputs_call(&inputs);
 
     info._impl.as_mut().unwrap().items.push(syn::ImplItem::Method(syn::parse2(quote!{
-        pub fn new( #(#inputs),* ) -> ::std::pin::Pin<Box<Self>> {
-            let mut this = Box::pin(Self { 
-                __real__: ::std::ptr::null_mut::<Self>(), 
-                _pinned: ::std::marker::PhantomPinned,
+        #[doc(hidden)]
+        #unpinned_doc
+        #[deny(unsafe_op_in_unsafe_fn)]
+        pub unsafe fn __new_unpinned__( #(#inputs),* ) -> Self {
+            Self { 
+                __real__: #null_real, 
+                _pinned: ::core::marker::PhantomPinned,
+                #borrow
                 #(#fields),*
-            });
-            unsafe { 
-                this.as_mut().get_unchecked_mut().__real__ =  this.as_mut().get_unchecked_mut();
-            };
+            }
+        }
+    }).unwrap()));
+
+    let unpinned = quote!{ unsafe { Self::__new_unpinned__( #(#inputs_call),* ) } };
+
+    if let Some(error) = validate_error {
+        let create_this = create_validated(info, unpinned, quote!{});
+        create_try_new(info, quote!{ #(#inputs),* }, inputs_call, quote!{
+            #create_this
+            Ok(this)
+        }, error);
+        return;
+    }
+    let create_this = create_pinned(info, unpinned);
+
+    info._impl.as_mut().unwrap().items.push(syn::ImplItem::Method(syn::parse2(quote!{
+        pub fn new( #(#inputs),* ) -> #pointer {
+            #create_this
             this
         }
     }).unwrap()));
 }
 
-fn create_new_with_parent(info: &mut ClassInfo, parent: &ClassInfo) {
+/// the method with <code>#\[validate\]</code>, which should return <code>Result<(), E></code>.
+fn get_validator(info: &ClassInfo) -> Option<ImplItemMethod> {
+    let validate = String::from("validate");
+    get_methods(info._impl.as_ref().unwrap()).into_iter().find(|x| get_meta_from_method(x).contains(&validate))
+}
+
+pub fn find_method(info: &ClassInfo, name: &str) -> Option<ImplItemMethod> {
+    get_methods(info._impl.as_ref().unwrap()).into_iter().find(|x| x.sig.ident == name)
+}
+
+/// <code>E</code> of a method which return <code>Result<T, E></code>.
+pub fn get_error_type(method: &ImplItemMethod) -> proc_macro2::TokenStream {
+    if let syn::ReturnType::Type(_, ty) = &method.sig.output {
+        if let syn::Type::Path(path) = ty.as_ref() {
+            let last = path.path.segments.last().unwrap();
+            if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
+                if last.ident == "Result" && args.args.len() == 2 {
+                    return args.args[1].to_token_stream();
+                }
+            }
+        }
+    }
+    syn::Error::new_spanned(&method.sig, "validator should return `Result<(), E>`").to_compile_error()
+}
+
+/// create <code>\_\_validate\_\_</code> which run validators from the root class to this class,
+/// return the error type if there is any validator.
+fn create_validate(info: &mut ClassInfo, parent: Option<&ClassInfo>) -> Option<proc_macro2::TokenStream> {
+    let validator = get_validator(info);
+    let parent_validate = parent.and_then(|x| find_method(x, "__validate__"));
+    let mut validates: Vec<proc_macro2::TokenStream> = Vec::new();
+    if parent_validate.is_some() {
+        validates.push(quote!{ self.__prototype__.__validate__()?; });
+    }
+    let error = match (&validator, &parent_validate) {
+        (Some(validator), _) => {
+            let validate = &validator.sig.ident;
+            validates.push(quote!{ self.#validate()?; });
+            get_error_type(validator)
+        },
+        (None, Some(parent_validate)) => get_error_type(parent_validate),
+        (None, None) => return None,
+    };
+    info._impl.as_mut().unwrap().items.push(syn::ImplItem::Method(syn::parse2(quote!{
+        #[doc(hidden)]
+        pub fn __validate__(&self) -> Result<(), #error> {
+            #(#validates)*
+            Ok(())
+        }
+    }).unwrap()));
+    Some(error)
+}
+
+fn get_inputs_call(inputs: &[FnArg]) -> Vec<Ident> {
+    inputs.iter().filter_map(|x| match x {
+        FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+            Pat::Ident(ident) => Some(ident.ident.clone()),
+            _ => None,
+        },
+        _ => None,
+    }).collect()
+}
+
+/// create <code>try_new</code> with <code>body</code>, and <code>new</code> which panic if <code>try_new</code> failed.
+fn create_try_new(info: &mut ClassInfo, inputs: proc_macro2::TokenStream, inputs_call: Vec<Ident>, body: proc_macro2::TokenStream, error: proc_macro2::TokenStream) {
+    let name = info.get_ident().to_string();
+    let pointer = info.get_pointer();
+    let _impl = info._impl.as_mut().unwrap();
+    _impl.items.push(syn::ImplItem::Method(syn::parse2(quote!{
+        pub fn try_new( #inputs ) -> Result<#pointer, #error> {
+            #body
+        }
+    }).unwrap()));
+    _impl.items.push(syn::ImplItem::Method(syn::parse2(quote!{
+        pub fn new( #inputs ) -> #pointer {
+            match Self::try_new( #(#inputs_call),* ) {
+                Ok(this) => this,
+                Err(err) => panic!("failed to create {}: {:?}", #name, err),
+            }
+        }
+    }).unwrap()));
+}
+
+/// pin <code>unpinned</code> as <code>this</code> and set <code>\_\_real\_\_</code>,
+/// or create <code>this</code> with <code>Rc::new\_cyclic</code> for class with <code>#\[rc\]</code> or <code>#\[arc\]</code>.
+pub fn create_pinned(info: &ClassInfo, unpinned: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if let Some((rc, weak)) = info.get_shared() {
+        let layouts = info.get_layouts();
+        let lhs: Vec<proc_macro2::TokenStream> = (0..layouts.len())
+            .map(|depth| get_prototype_mut(&layouts, depth, quote!{ this })).collect();
+        return quote!{
+            let this = #rc::new_cyclic(|__weak__: &#weak<Self>| {
+                let mut this: Self = #unpinned;
+                #( #lhs .__real__ = __weak__.clone(); )*
+                this
+            });
+        };
+    }
+    let real_setter = create_real_setter(info);
+    let _box = info.get_box();
+    quote!{
+        let this = #_box::pin( #unpinned );
+        #real_setter
+    }
+}
+
+/// write the raw pointer <code>\_\_real\_\_</code> to <code>\_\_real\_\_</code> of the object it points to and all its prototypes.
+fn get_real_writes(info: &ClassInfo) -> Vec<proc_macro2::TokenStream> {
+    (0..info.get_layouts().len()).map(|depth| {
+        let place = info.get_prototype_ptr(depth, quote!{ __real__ });
+        quote!{
+            ::core::ptr::addr_of_mut!((*#place).__real__).write(__real__);
+        }
+    }).collect()
+}
+
+/// set <code>\_\_real\_\_</code> of <code>this</code> and all its prototypes through one raw pointer,
+/// so no reference to <code>this</code> is created between deriving and storing <code>\_\_real\_\_</code>.
+fn create_real_setter(info: &ClassInfo) -> proc_macro2::TokenStream {
+    let real_setter = get_real_writes(info);
+    let _box = info.get_box();
+    quote!{
+        let this = unsafe {
+            let __real__ = #_box::into_raw(::core::pin::Pin::into_inner_unchecked(this));
+            #(#real_setter)*
+            ::core::pin::Pin::new_unchecked(#_box::from_raw(__real__))
+        };
+    }
+}
+
+/// create <code>this</code> like <code>create\_pinned</code> if <code>\_\_validate\_\_</code> of <code>unpinned</code> passes, its error is mapped by <code>map\_err</code>.
+/// 
+/// validators run from the root class to this class while <code>\_\_real\_\_</code> points to the value on the stack,
+/// so nothing is allocated for the object if any of them fails.
+/// class with <code>#\[rc\]</code> or <code>#\[arc\]</code> is validated after it is created, because its <code>\_\_real\_\_</code> is a <code>Weak</code>.
+pub fn create_validated(info: &ClassInfo, unpinned: proc_macro2::TokenStream, map_err: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if info.get_shared().is_some() {
+        let create_this = create_pinned(info, unpinned);
+        return quote!{
+            #create_this
+            this.__validate__() #map_err ?;
+        };
+    }
+    let real_writes = get_real_writes(info);
+    let create_this = create_pinned(info, quote!{ __value__ });
+    quote!{
+        let __value__ = {
+            let mut __value__: Self = #unpinned;
+            // `__real__` is set again after the value is pinned.
+            unsafe {
+                let __real__: *mut Self = &mut __value__;
+                #(#real_writes)*
+            }
+            __value__.__validate__() #map_err ?;
+            __value__
+        };
+        #create_this
+    }
+}
+
+/// rename a parameter of parent's <code>new</code> which has the same name with a field,
+/// like <code>name</code> to <code>vehicle_name</code> where <code>name</code> is declared in <code>Vehicle</code>.
+fn get_super_input_name(info: &ClassInfo, input: &Ident, parent: &ClassInfo) -> Ident {
+    let owner = info.get_mro().iter()
+        .find(|x| x.get_fields().iter().any(|field| field.ident.as_ref() == Some(input)))
+        .map(|x| x.get_ident())
+        .unwrap_or_else(|| parent.get_ident());
+    format_ident!("{}_{}", to_snake_case(&owner), input)
+}
+
+fn create_new_with_parent(info: &mut ClassInfo, parent: &ClassInfo, validate_error: Option<proc_macro2::TokenStream>) {
+    let (inputs, fields) = get_new_fields(info);
+    let null_real = info.get_null_real();
+    let pointer = info.get_pointer();
     let _struct = info._struct.as_ref().unwrap();
-    let mut inputs: Vec<FnArg> = Vec::new();
     let mut parent_inputs: Vec<FnArg> = Vec::new();
     let mut parent_inputs_call: Vec<Ident> = Vec::new();
-    let mut fields: Vec<FieldValue> = Vec::new();
-    for field in &_struct.fields {
-        if field.ident.as_ref().is_some() {
-            let id = field.ident.as_ref().unwrap().clone();
-            let ty = field.ty.clone();
-            
-            inputs.push(syn::parse2(quote!{#id: #ty}).unwrap());
-            fields.push(syn::parse2(quote!{#id: #id}).unwrap());
-        } else {
-            panic!("not implement for unnamed field!");
-        }
-    }
     
+    let own_inputs: Vec<Ident> = _struct.fields.iter()
+        .filter(|x| get_default(x).is_none())
+        .filter_map(|x| x.ident.clone()).collect();
     for item in &parent._impl.as_ref().unwrap().items {
         match item {
             ImplItem::Method(x) => {
@@ -92,18 +384,22 @@ fn create_new_with_parent(info: &mut ClassInfo, parent: &ClassInfo) {
                     continue;
                 }
                 for arg in &x.sig.inputs {
-                    parent_inputs.push(arg.clone());
-                    match arg {
+                    let mut arg = arg.clone();
+                    match &mut arg {
                         FnArg::Receiver(_) => {},
                         FnArg::Typed(pat_type) => {
-                            match *pat_type.pat.clone() {
+                            match pat_type.pat.as_mut() {
                                 Pat::Ident(ident) => {
+                                    if own_inputs.contains(&ident.ident) {
+                                        ident.ident = get_super_input_name(info, &ident.ident, parent);
+                                    }
                                     parent_inputs_call.push(ident.ident.clone());
                                 },
                                 _ => { },
                             }
                         },
                     }
+                    parent_inputs.push(arg);
                 }
             },
             _ => {},
@@ -112,46 +408,233 @@ fn create_new_with_parent(info: &mut ClassInfo, parent: &ClassInfo) {
 
     let parent_type = parent.get_ident();
 
-    let rhs = quote!{
-        .as_mut().get_unchecked_mut().__real__ =  this.as_mut().get_unchecked_mut();
-    };
-    let mut lhs = quote!{
-        this
-    };
-    let mut real_setter: Vec<proc_macro2::TokenStream> = Vec::new();
-    for _ in info.get_mro() {
-        lhs = quote!{
-            #lhs .as_mut().get_unchecked_mut().__prototype__
-        };
-        real_setter.push(quote!{
-            #lhs #rhs
-        });
-    }
-
     let new_inputs = if parent_inputs.len() > 0 {
         quote!{ #(#parent_inputs),* ,  #(#inputs),* }
     } else {
         quote!{ #(#inputs),* }
     };
+    let mut inputs_call = parent_inputs_call.clone();
+    inputs_call.append(&mut get_inputs_call(&inputs));
 
+    let create_prototype = if info.is_inline() {
+        quote!{ unsafe { #parent_type ::__new_unpinned__( #(#parent_inputs_call),* ) } }
+    } else if find_method(parent, "__validate__").is_some() {
+        // the parent is validated with this class by `__validate__`, not when it is created.
+        let _box = info.get_box();
+        quote!{ #_box::pin(unsafe { #parent_type ::__new_unpinned__( #(#parent_inputs_call),* ) }) }
+    } else {
+        quote!{ #parent_type ::new( #(#parent_inputs_call),* ) }
+    };
+    let unpinned_doc = get_unpinned_doc();
     info._impl.as_mut().unwrap().items.push(syn::ImplItem::Method(syn::parse2(quote!{
-        pub fn new( #new_inputs ) -> ::std::pin::Pin<Box<Self>> {
-            let __prototype__ = #parent_type ::new( #(#parent_inputs_call),* );
-            let mut this = Box::pin(Self { 
-                __prototype__,
-                __real__: ::std::ptr::null_mut::<Self>(), 
-                _pinned: ::std::marker::PhantomPinned,
+        #[doc(hidden)]
+        #unpinned_doc
+        #[deny(unsafe_op_in_unsafe_fn)]
+        pub unsafe fn __new_unpinned__( #new_inputs ) -> Self {
+            Self { 
+                __prototype__: #create_prototype,
+                __real__: #null_real, 
+                _pinned: ::core::marker::PhantomPinned,
                 #(#fields),*
-            });
-            unsafe { 
-                this.as_mut().get_unchecked_mut().__real__ =  this.as_mut().get_unchecked_mut();
-                #(#real_setter);*
-            };
+            }
+        }
+    }).unwrap()));
+
+    let unpinned = quote!{ unsafe { Self::__new_unpinned__( #(#inputs_call),* ) } };
+
+    if let Some(error) = validate_error {
+        let create_this = create_validated(info, unpinned, quote!{});
+        let body = quote!{
+            #create_this
+            Ok(this)
+        };
+        create_try_new(info, new_inputs, inputs_call, body, error);
+        return;
+    }
+    let create_this = create_pinned(info, unpinned);
+
+    info._impl.as_mut().unwrap().items.push(syn::ImplItem::Method(syn::parse2(quote!{
+        pub fn new( #new_inputs ) -> #pointer {
+            #create_this
             this
         }
     }).unwrap()));
 }
 
+fn take_constructors(info: &mut ClassInfo) -> Vec<ImplItemMethod> {
+    let constructor = String::from("constructor");
+    let _impl = info._impl.as_mut().unwrap();
+    let constructors: Vec<ImplItemMethod> = get_methods(_impl).into_iter()
+        .filter(|x| get_meta_from_method(x).contains(&constructor)).collect();
+    let signatures: Vec<String> = constructors.iter().map(get_signature_string).collect();
+    _impl.items.retain(|item| match item {
+        syn::ImplItem::Method(method) => !signatures.contains(&get_signature_string(method)),
+        _ => true,
+    });
+    constructors
+}
+
+fn get_macro(stmt: &Stmt) -> Option<&Macro> {
+    match stmt {
+        Stmt::Item(Item::Macro(x)) => Some(&x.mac),
+        Stmt::Expr(Expr::Macro(x)) | Stmt::Semi(Expr::Macro(x), _) => Some(&x.mac),
+        _ => None,
+    }
+}
+
+/// convert a method with <code>#\[constructor\]</code> to an unpinned constructor and a constructor calling it,
+/// with <code>try\_xxx</code> returning the error of validators if there is any.
+/// 
+/// <code>super_new!(...)</code> will be convert to create <code>\_\_prototype\_\_</code>,
+/// and the last expression <code>Self { ... }</code> will be pinned with <code>\_\_real\_\_</code> set.
+fn create_constructor(info: &ClassInfo, parent: Option<&ClassInfo>, mut method: ImplItemMethod) -> syn::Result<Vec<ImplItemMethod>> {
+    let name = info.get_ident();
+    let mut stmts = std::mem::take(&mut method.block.stmts);
+    let tail = match stmts.pop() {
+        Some(Stmt::Expr(Expr::Struct(tail))) if tail.path.is_ident("Self") || tail.path.is_ident(&name) => tail,
+        _ => return Err(syn::Error::new_spanned(&method.sig, 
+            format!("constructor of {} should end with `Self {{ ... }}`", name))),
+    };
+    if let Some(rest) = &tail.rest {
+        return Err(syn::Error::new_spanned(rest, "constructor can not use `..` to fill fields"));
+    }
+
+    let mut has_super_new = false;
+    for stmt in &mut stmts {
+        let mac = match get_macro(stmt) {
+            Some(mac) if mac.path.is_ident("super_new") => mac,
+            _ => continue,
+        };
+        let parent = match parent {
+            Some(parent) => parent,
+            None => return Err(syn::Error::new_spanned(mac, format!("{} has no parent to call `super_new!`", name))),
+        };
+        if has_super_new {
+            return Err(syn::Error::new_spanned(mac, "`super_new!` should be called only once"));
+        }
+        has_super_new = true;
+        let super_new: SuperNew = mac.parse_body()?;
+        let constructor = super_new.constructor.unwrap_or_else(|| syn::Ident::new("new", proc_macro2::Span::call_site()));
+        let unpinned = get_unpinned(&constructor);
+        let has_unpinned = find_method(parent, &unpinned.to_string()).is_some();
+        let parent = parent.get_ident();
+        let args = super_new.args;
+        *stmt = if info.is_inline() || has_unpinned {
+            // arguments are evaluated out of the unsafe block.
+            // a boxed parent is pinned without validation too, it is validated with this class by `__validate__`.
+            let names: Vec<Ident> = (0..args.len()).map(|x| format_ident!("__arg{}__", x)).collect();
+            let args = args.iter();
+            let create = if info.is_inline() {
+                quote!{ unsafe { #parent :: #unpinned ( #(#names),* ) } }
+            } else {
+                let _box = info.get_box();
+                quote!{ #_box::pin(unsafe { #parent :: #unpinned ( #(#names),* ) }) }
+            };
+            syn::parse2(quote!{
+                let __prototype__ = {
+                    #(let #names = #args;)*
+                    #create
+                };
+            })?
+        } else {
+            syn::parse2(quote!{
+                let __prototype__ = #parent :: #constructor ( #args );
+            })?
+        };
+    }
+    if parent.is_some() && !has_super_new {
+        return Err(syn::Error::new_spanned(&method.sig, 
+            format!("constructor of {} should call `super_new!` to create its parent", name)));
+    }
+
+    let prototype = if parent.is_some() {
+        quote!{ __prototype__, }
+    } else {
+        quote!{}
+    };
+    let mut fields = tail.fields;
+    let null_real = info.get_null_real();
+    let pointer = info.get_pointer();
+    let borrow = get_borrow_init(info);
+    for field in &info._struct.as_ref().unwrap().fields {
+        let id = field.ident.as_ref().unwrap();
+        let assigned = fields.iter().any(|x| match &x.member {
+            syn::Member::Named(member) => member == id,
+            _ => false,
+        });
+        if assigned {
+            continue;
+        }
+        if let Some(default) = get_default(field) {
+            fields.push(syn::parse2(quote!{#id: #default})?);
+        }
+    }
+
+    let mut unpinned = method.clone();
+    let unpinned_doc = get_unpinned_doc();
+    unpinned.attrs = syn::Attribute::parse_outer.parse2(quote!{
+        #[doc(hidden)]
+        #unpinned_doc
+        #[deny(unsafe_op_in_unsafe_fn)]
+    })?;
+    unpinned.vis = syn::parse_quote!{ pub };
+    unpinned.sig.unsafety = Some(Default::default());
+    unpinned.sig.ident = get_unpinned(&method.sig.ident);
+    unpinned.sig.output = syn::parse2(quote!{ -> Self })?;
+    stmts.push(Stmt::Expr(syn::parse2(quote!{
+        Self { 
+            #prototype
+            __real__: #null_real, 
+            _pinned: ::core::marker::PhantomPinned,
+            #borrow
+            #fields
+        }
+    })?));
+    unpinned.block.stmts = stmts;
+
+    let unpinned_ident = &unpinned.sig.ident;
+    let inputs_call = get_inputs_call(&method.sig.inputs.iter().cloned().collect::<Vec<FnArg>>());
+    let unpinned_call = quote!{ unsafe { Self:: #unpinned_ident ( #(#inputs_call),* ) } };
+    let validate = match find_method(info, "__validate__") {
+        Some(validate) => validate,
+        None => {
+            let create_this = create_pinned(info, unpinned_call);
+            method.block = syn::parse2(quote!{
+                {
+                    #create_this
+                    this
+                }
+            })?;
+            method.sig.output = syn::parse2(quote!{ -> #pointer })?;
+            return Ok(vec![unpinned, method]);
+        },
+    };
+    // unpinned constructors never validate, so the whole hierarchy is validated here.
+    let error = get_error_type(&validate);
+    let mut try_method = method.clone();
+    try_method.sig.ident = format_ident!("try_{}", method.sig.ident);
+    try_method.sig.output = syn::parse2(quote!{ -> Result<#pointer, #error> })?;
+    let create_this = create_validated(info, unpinned_call, quote!{});
+    try_method.block = syn::parse2(quote!{
+        {
+            #create_this
+            Ok(this)
+        }
+    })?;
+    let try_ident = &try_method.sig.ident;
+    let name = name.to_string();
+    method.block = syn::parse2(quote!{
+        {
+            match Self:: #try_ident ( #(#inputs_call),* ) {
+                Ok(this) => this,
+                Err(err) => panic!("failed to create {}: {:?}", #name, err),
+            }
+        }
+    })?;
+    method.sig.output = syn::parse2(quote!{ -> #pointer })?;
+    Ok(vec![unpinned, try_method, method])
+}
+
 pub fn parse_class(info: &mut ClassInfo) {
     let has_parent = info._parent.is_some();
     let parent: Option<Ident> = if has_parent {
@@ -159,35 +642,109 @@ pub fn parse_class(info: &mut ClassInfo) {
     } else {
         None
     };
-    if has_parent {
-        let p = &info.get_parent_info();
+    reflect::create_meta(info);
+    reflect::create_invoke(info);
+    derive::create_clone_dyn(info);
+    derive::create_as_any(info);
+    reflect::create_reflection(info);
+    reflect::create_metaclass(info);
+    let constructors = take_constructors(info);
+    let has_new = constructors.iter().any(|x| x.sig.ident == "new");
+    let parent_info = if has_parent {
+        Some(info.get_parent_info())
+    } else {
+        None
+    };
+    if let Some(p) = &parent_info {
         parse_impl_with_parent(info, p);
-        create_new_with_parent(info, p);
+        let validate_error = create_validate(info, Some(p));
+        if !has_new {
+            create_new_with_parent(info, p, validate_error);
+        }
     } else {
         parse_impl(info);
-        create_new(info)
+        let validate_error = create_validate(info, None);
+        if !has_new {
+            create_new(info, validate_error)
+        }
+    }
+    for mut constructor in constructors {
+        remove_meta_from_method(&mut constructor, &String::from("constructor"));
+        let methods = match create_constructor(info, parent_info.as_ref(), constructor.clone()) {
+            Ok(methods) => methods,
+            Err(err) => {
+                let err = err.to_compile_error();
+                constructor.block = syn::parse2(quote!{ { #err } }).unwrap();
+                vec![constructor]
+            }
+        };
+        for method in methods {
+            info._impl.as_mut().unwrap().items.push(ImplItem::Method(method));
+        }
     }
 
-    let keep = String::from("keep");
+    let validate = String::from("validate");
     for item in &mut info._impl.as_mut().unwrap().items {
         match item {
             syn::ImplItem::Method(method) => {
-                remove_meta_from_method(method, &keep)
+                remove_meta_from_method(method, &validate);
             },
             _ => {},
         }
     }
+    if let Some(p) = &parent_info {
+        inherit_kept_methods(info, p);
+    }
+    
+    if info.get_shared().is_some() {
+        create_shared(info);
+    }
+    if info.has_attr("track_borrows") {
+        create_tracker(info);
+    }
+    if info.has_attr("builder") {
+        builder::create_builder(info);
+    }
+    derive::create_derives(info);
+    if info.get_fields().iter().any(|x| x.attrs.iter().any(|attr| attr.path.is_ident("pin"))) {
+        create_projection(info);
+    }
+    if has_parent && info.inherits_attr("deref") {
+        create_deref(info);
+    }
+    registry::create_registration(info);
     
+    let inline = info.is_inline();
+    let shared = info.get_shared();
+    let tracker = info.get_tracker();
+    let _box = info.get_box();
     let mut _struct = info._struct.as_mut().unwrap();
     match _struct.fields {
         syn::Fields::Named(ref mut fields) => {
-            if has_parent {
+            if has_parent && inline {
+                let p = &parent.unwrap();
+                fields.named.push(Field::parse_named.parse2(quote!{__prototype__: #p}).unwrap());
+            } else if has_parent {
                 let p = &parent.unwrap();
-                fields.named.push(Field::parse_named.parse2(quote!{__prototype__: ::std::pin::Pin<Box<#p>>}).unwrap());
+                fields.named.push(Field::parse_named.parse2(quote!{__prototype__: ::core::pin::Pin<#_box<#p>>}).unwrap());
             }
             let real = syn::Ident::new(&format!("__{}__", _struct.ident), proc_macro2::Span::call_site());
-            fields.named.push(Field::parse_named.parse2(quote!{__real__: *mut dyn #real}).unwrap());
-            fields.named.push(Field::parse_named.parse2(quote!{_pinned: ::std::marker::PhantomPinned}).unwrap());
+            if let (false, Some(tracker)) = (has_parent, &tracker) {
+                let state = format_ident!("{}State__", tracker.to_string().trim_end_matches("__"));
+                fields.named.push(Field::parse_named.parse2(quote!{__borrow__: #state}).unwrap());
+            }
+            let real_field = match &shared {
+                Some((_, weak)) => Field::parse_named.parse2(quote!{__real__: #weak<dyn #real>}).unwrap(),
+                None => Field::parse_named.parse2(quote!{__real__: *mut dyn #real}).unwrap(),
+            };
+            if has_parent && inline {
+                // <code>\_\_real\_\_</code> is the first field of <code>#[repr(C)]</code>,
+                // so an inline prototype never has the same address with the object containing it.
+                fields.named.insert(0, real_field);
+            } else {
+                fields.named.push(real_field);
+            }
+            fields.named.push(Field::parse_named.parse2(quote!{_pinned: ::core::marker::PhantomPinned}).unwrap());
         },
         syn::Fields::Unnamed(_) => {
             panic!("not support struct ({}) with unnamed field.", _struct.ident.to_string());
@@ -196,6 +753,256 @@ pub fn parse_class(info: &mut ClassInfo) {
             panic!("not support None ({}).", _struct.ident.to_string());
         },
     }
+
+    if has_parent && inline {
+        _struct.attrs.push(syn::parse_quote!{ #[repr(C)] });
+    }
+
+    if info.is_thread_safe() {
+        create_thread_safe(info);
+    }
+}
+
+/// implement <code>Send</code> and <code>Sync</code> for class with <code>#\[thread_safe\]</code>,
+/// bounded by types of all fields except <code>\_\_real\_\_</code>, which is guaranteed by the supertraits of the real trait.
+fn create_thread_safe(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    if info.get_shared().is_some() {
+        if !info.inherits_attr("arc") {
+            let err = syn::Error::new_spanned(&name, format!("{} can not use `#[thread_safe]` with `#[rc]`, use `#[arc]` instead", name)).to_compile_error();
+            info.extra_items.push(syn::parse2(quote!{ #err }).unwrap());
+        }
+        // Arc and Weak are Send and Sync when the real trait is.
+        return;
+    }
+    let _struct = info._struct.as_ref().unwrap();
+    let types: Vec<&syn::Type> = _struct.fields.iter()
+        .filter(|x| x.ident.as_ref().is_none_or(|x| x != "__real__"))
+        .map(|x| &x.ty).collect();
+    let (impl_generics, ty_generics, where_clause) = _struct.generics.split_for_impl();
+    let predicates: Vec<proc_macro2::TokenStream> = where_clause.iter()
+        .flat_map(|x| x.predicates.iter().map(|x| x.to_token_stream())).collect();
+    let mut items: Vec<Item> = Vec::new();
+    for _trait in [quote!{ Send }, quote!{ Sync }] {
+        items.push(syn::parse2(quote!{
+            unsafe impl #impl_generics #_trait for #name #ty_generics where #(#predicates,)* #(#types: #_trait),* {}
+        }).unwrap());
+    }
+    info.extra_items.append(&mut items);
+}
+
+/// create accessors from <code>Pin<&mut Self></code> for fields of class with any <code>#\[pin\]</code> field,
+/// <code>xxx_pin</code> return <code>Pin<&mut T></code> for fields with <code>#\[pin\]</code>, and <code>xxx_mut</code> return <code>&mut T</code> for the rest.
+/// 
+/// <code>Unpin</code> is implemented only if the pinned fields, the inline prototype and <code>\_pinned</code> are <code>Unpin</code>,
+/// which never holds as <code>\_\_real\_\_</code> points to the object, and conflicts with an <code>Unpin</code> implemented by user.
+fn create_projection(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    let unpin = format_ident!("__{}Unpin__", name);
+    let mut pinned: Vec<syn::Type> = Vec::new();
+    let mut accessors: Vec<ImplItem> = Vec::new();
+    for field in info.get_fields() {
+        let id = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let accessor = if field.attrs.iter().any(|x| x.path.is_ident("pin")) {
+            pinned.push(ty.clone());
+            let name = format_ident!("{}_pin", id);
+            let doc = format!(" <code>{}</code> pinned like this object, it is never moved while this object is pinned, as this object is never <code>Unpin</code>.", id);
+            quote!{
+                #[doc = #doc]
+                /// 
+                /// <code>Drop</code> of this class should not move it out of <code>&mut self</code>.
+                pub fn #name(self: ::core::pin::Pin<&mut Self>) -> ::core::pin::Pin<&mut #ty> {
+                    unsafe { self.map_unchecked_mut(|x| &mut x.#id) }
+                }
+            }
+        } else {
+            let name = format_ident!("{}_mut", id);
+            let doc = format!(" <code>{}</code> of this pinned object, it is not structurally pinned and can be moved.", id);
+            quote!{
+                #[doc = #doc]
+                pub fn #name(self: ::core::pin::Pin<&mut Self>) -> &mut #ty {
+                    unsafe { &mut self.get_unchecked_mut().#id }
+                }
+            }
+        };
+        accessors.push(syn::parse2(accessor).unwrap());
+    }
+    if info.is_inline() {
+        let prototype = info._struct.as_ref().unwrap().fields.iter()
+            .find(|x| x.ident.as_ref().is_some_and(|x| x == "__prototype__")).unwrap();
+        pinned.push(prototype.ty.clone());
+    }
+    let generics = &info._struct.as_ref().unwrap().generics;
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let mut unpin_generics = generics.clone();
+    unpin_generics.params.insert(0, syn::parse_quote!{ '__pin });
+    let (unpin_impl_generics, unpin_ty_generics, _) = unpin_generics.split_for_impl();
+    let pinned_ids = (0..pinned.len()).map(|x| format_ident!("__{}", x));
+    let predicates = where_clause.map(|x| x.predicates.iter().collect::<Vec<_>>()).unwrap_or_default();
+    info.extra_items.push(syn::parse2(quote!{
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        pub struct #unpin #unpin_impl_generics #where_clause {
+            __pin__: ::core::marker::PhantomData<(&'__pin (), fn() -> #name #ty_generics)>,
+            _pinned: ::core::marker::PhantomPinned,
+            #(#pinned_ids: #pinned),*
+        }
+    }).unwrap());
+    info.extra_items.push(syn::parse2(quote!{
+        impl #unpin_impl_generics ::core::marker::Unpin for #name #ty_generics
+            where #(#predicates,)* #unpin #unpin_ty_generics: ::core::marker::Unpin {}
+    }).unwrap());
+    info._impl.as_mut().unwrap().items.extend(accessors);
+}
+
+/// implement <code>Deref</code> to the parent for class with <code>#\[deref\]</code> in itself or any ancestor,
+/// and create <code>parent_pin</code> returning <code>Pin<&mut Parent></code> from <code>Pin<&mut Self></code> unless the class is shared.
+/// 
+/// <code>DerefMut</code> is not implemented, as <code>&mut Parent</code> could be used to move the parent out of the chain.
+fn create_deref(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    let parent = info._parent.as_ref().unwrap().parent.clone();
+    let (impl_generics, ty_generics, where_clause) = info._struct.as_ref().unwrap().generics.split_for_impl();
+    let (prototype, prototype_pin) = if info.is_inline() {
+        (quote!{ &self.__prototype__ }, quote!{ unsafe { self.map_unchecked_mut(|x| &mut x.__prototype__) } })
+    } else {
+        (quote!{ &*self.__prototype__ }, quote!{ unsafe { self.get_unchecked_mut().__prototype__.as_mut() } })
+    };
+    let deref = quote!{
+        impl #impl_generics ::core::ops::Deref for #name #ty_generics #where_clause {
+            type Target = #parent;
+            fn deref(&self) -> &#parent {
+                #prototype
+            }
+        }
+    };
+    info.extra_items.push(syn::parse2(deref).unwrap());
+    if info.get_shared().is_none() {
+        info._impl.as_mut().unwrap().items.push(syn::parse2(quote!{
+            /// the parent of this class, pinned like this class.
+            pub fn parent_pin(self: ::core::pin::Pin<&mut Self>) -> ::core::pin::Pin<&mut #parent> {
+                #prototype_pin
+            }
+        }).unwrap());
+    }
+}
+
+/// create <code>this\_rc</code> and <code>this\_weak</code> for class with <code>#\[rc\]</code> or <code>#\[arc\]</code>.
+fn create_shared(info: &mut ClassInfo) {
+    let (rc, weak) = info.get_shared().unwrap();
+    let real = info.get_real();
+    let name = info.get_ident();
+    let parent_shared = info._parent.is_none() || info.get_parent_info().get_shared().is_some();
+    if !parent_shared {
+        let err = syn::Error::new_spanned(&name, format!("`#[rc]` and `#[arc]` of {} should be used on the root class", name)).to_compile_error();
+        info.extra_items.push(syn::parse2(quote!{ #err }).unwrap());
+    }
+    if info.is_static() {
+        let err = syn::Error::new_spanned(&name, format!("{} can not use `#[static_dispatch]` with `#[rc]` or `#[arc]`", name)).to_compile_error();
+        info.extra_items.push(syn::parse2(quote!{ #err }).unwrap());
+    }
+    let _impl = info._impl.as_mut().unwrap();
+    _impl.items.push(syn::parse2(quote!{
+        /// the most derived object, panic if it is being dropped.
+        pub fn this_rc(&self) -> #rc<dyn #real> {
+            self.__real__.upgrade().unwrap()
+        }
+    }).unwrap());
+    _impl.items.push(syn::parse2(quote!{
+        pub fn this_weak(&self) -> #weak<dyn #real> {
+            self.__real__.clone()
+        }
+    }).unwrap());
+}
+
+/// create the guard of borrows for the root class with <code>#\[track_borrows\]</code>,
+/// which panic on conflicting borrows in debug builds and only dereference in release builds.
+/// 
+/// the state is <code>(frames, borrow, last)</code>, where <code>frames</code> is the count of running methods with <code>&self</code>,
+/// <code>borrow</code> is the count of <code>self</code> or <code>-1</code> for <code>self\_mut</code>.
+fn create_tracker(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    if info._parent.is_some() {
+        let err = syn::Error::new_spanned(&name, format!("`#[track_borrows]` of {} should be used on the root class", name)).to_compile_error();
+        info.extra_items.push(syn::parse2(quote!{ #err }).unwrap());
+        return;
+    }
+    if info.is_thread_safe() {
+        let err = syn::Error::new_spanned(&name, format!("{} can not use `#[thread_safe]` with `#[track_borrows]`", name)).to_compile_error();
+        info.extra_items.push(syn::parse2(quote!{ #err }).unwrap());
+    }
+    let tracker = info.get_tracker().unwrap();
+    let state = format_ident!("{}State__", tracker.to_string().trim_end_matches("__"));
+    let vis = &info._struct.as_ref().unwrap().vis;
+    let items: Vec<Item> = vec![
+        syn::parse_quote!{
+            #[doc(hidden)]
+            #[cfg(debug_assertions)]
+            #vis type #state = ::core::cell::Cell<(usize, isize, &'static str)>;
+        },
+        syn::parse_quote!{
+            #[doc(hidden)]
+            #[cfg(not(debug_assertions))]
+            #vis type #state = ();
+        },
+        syn::parse_quote!{
+            #[doc(hidden)]
+            #vis struct #tracker<T: ?Sized> {
+                value: *mut T,
+                #[cfg(debug_assertions)]
+                state: *const #state,
+                #[cfg(debug_assertions)]
+                last: (usize, isize, &'static str),
+            }
+        },
+        syn::parse_quote!{
+            impl<T: ?Sized> #tracker<T> {
+                #[allow(unused_variables)]
+                pub fn new(value: *mut T, state: &#state, mutable: Option<bool>, name: &'static str) -> Self {
+                    #[cfg(debug_assertions)]
+                    {
+                        let last = state.get();
+                        let (frames, borrow, by) = last;
+                        match mutable {
+                            None => state.set((frames + 1, borrow, name)),
+                            Some(false) if borrow < 0 => panic!("{} can not borrow `self` while it is mutably borrowed by {}", name, by),
+                            Some(false) => state.set((frames, borrow + 1, name)),
+                            Some(true) if frames > 0 || borrow != 0 => panic!("{} can not borrow `self_mut` while it is borrowed by {}", name, by),
+                            Some(true) => state.set((frames, -1, name)),
+                        }
+                        Self { value, state, last }
+                    }
+                    #[cfg(not(debug_assertions))]
+                    Self { value }
+                }
+            }
+        },
+        syn::parse_quote!{
+            impl<T: ?Sized> Drop for #tracker<T> {
+                fn drop(&mut self) {
+                    #[cfg(debug_assertions)]
+                    unsafe { (*self.state).set(self.last) };
+                }
+            }
+        },
+        syn::parse_quote!{
+            impl<T: ?Sized> ::core::ops::Deref for #tracker<T> {
+                type Target = T;
+                fn deref(&self) -> &T {
+                    unsafe { &*self.value }
+                }
+            }
+        },
+        syn::parse_quote!{
+            impl<T: ?Sized> ::core::ops::DerefMut for #tracker<T> {
+                fn deref_mut(&mut self) -> &mut T {
+                    unsafe { &mut *self.value }
+                }
+            }
+        },
+    ];
+    info.extra_items.extend(items);
 }
 
 fn parse_impl(info: &mut ClassInfo) {
@@ -207,6 +1014,10 @@ fn parse_impl_with_parent(info: &mut ClassInfo, parent: &ClassInfo) {
     move_methods_to_prototype(info);
     parse_impl(info);
     retrieve_implements_from_parent(info, parent);
+    let ctx = ExprContext::new(info);
+    let is_static = info.is_static();
+    let real = info.get_real();
+    let mro = info.get_mro();
     for _trait_ident in info._trait_impl.clone().keys() {
         let _trait_ident = _trait_ident.clone();
         let o_prototype = parent._trait_impl.get(&_trait_ident.clone());
@@ -218,7 +1029,11 @@ fn parse_impl_with_parent(info: &mut ClassInfo, parent: &ClassInfo) {
         let mut override_methods = get_methods(&sub_impl);
 
         for method in &mut override_methods {
-            parse_expr::parse_block(&mut method.block);
+            if is_static {
+                create_static_helper(&ctx, info._impl.as_mut().unwrap(), &_trait_ident, method);
+            } else {
+                parse_expr::parse_method(method, &ctx);
+            }
             let find = prototype_methods.iter_mut().position(|x| get_signature_string(x) == get_signature_string(method));
             if find.is_some() {
                 prototype_methods.remove(find.unwrap());
@@ -253,16 +1068,35 @@ fn parse_impl_with_parent(info: &mut ClassInfo, parent: &ClassInfo) {
                 }
             }
 
-            let _super = if is_mut {
-                quote!{ unsafe { self.__prototype__.as_mut().get_unchecked_mut() } }
-            } else {
-                quote!{ self.__prototype__ }
+            let helper = get_static_helper(&_trait_ident, method_name);
+            let depth = mro.iter().position(|x| find_method(x, &helper.to_string()).is_some()).map(|x| x + 1);
+            let body = match depth {
+                Some(depth) if is_static && parse_expr::get_ref_receiver(method).is_some() => {
+                    let prototype = if is_mut {
+                        parse_expr::get_super_mut(&ctx, depth)
+                    } else {
+                        let prototypes = vec![quote!{__prototype__}; depth];
+                        quote!{ self #(.#prototypes)* }
+                    };
+                    parse_expr::branch_on_real(is_mut, quote!{ #prototype . #helper ::<Self>( __real__, #(#inputs),* ) },
+                        quote!{ #prototype . #helper ::<dyn #real>( __real__, #(#inputs),* ) })
+                },
+                _ => {
+                    let _super = if is_mut {
+                        parse_expr::get_super_mut(&ctx, 1)
+                    } else {
+                        quote!{ self.__prototype__ }
+                    };
+                    quote!{
+                        #_super . #method_name ( #(#inputs),* )
+                    }
+                },
             };
 
             let override_method: ImplItemMethod = syn::parse2(quote!{
                 #(#attrs)*
                 #sign {
-                    #_super . #method_name ( #(#inputs),* )
+                    #body
                 }
             }).unwrap();
             
@@ -277,6 +1111,64 @@ fn parse_impl_with_parent(info: &mut ClassInfo, parent: &ClassInfo) {
 
 }
 
+/// forward methods with <code>#\[keep\]</code> of the parent, including the ones it inherits, to the class,
+/// unless the class declares a method with the same name.
+/// 
+/// methods with <code>&self</code> or <code>&mut self</code> call the ones of <code>\_\_prototype\_\_</code>, and associated functions call <code>Parent::xxx</code>,
+/// methods using <code>Self</code> in the signature are not inherited because <code>Self</code> of them is the parent,
+/// they are named in the doc of the class instead.
+fn inherit_kept_methods(info: &mut ClassInfo, parent: &ClassInfo) {
+    let ctx = ExprContext::new(info);
+    let parent_name = parent.get_ident();
+    let mut names: Vec<String> = get_methods(info._impl.as_ref().unwrap()).iter().map(|x| x.sig.ident.to_string()).collect();
+    if let Some(real_trait) = &info.real_trait {
+        names.extend(real_trait.items.iter().filter_map(|x| match x {
+            TraitItem::Method(x) => Some(x.sig.ident.to_string()),
+            _ => None,
+        }));
+    }
+    let keep = String::from("keep");
+    for method in get_methods(parent._impl.as_ref().unwrap()) {
+        let method_name = &method.sig.ident;
+        if !get_meta_from_method(&method).contains(&keep) || names.contains(&method_name.to_string()) {
+            continue;
+        }
+        if reflect::uses_self_type(method.sig.to_token_stream()) {
+            let doc = format!(" <code>{}</code> of <code>{}</code> is not inherited because it uses <code>Self</code>, declare it in this class to use it.", method_name, parent_name);
+            info._struct.as_mut().unwrap().attrs.push(syn::parse_quote!{ #[doc = #doc] });
+            continue;
+        }
+        let inputs: Vec<Ident> = method.sig.inputs.iter().filter_map(|x| match x {
+            FnArg::Typed(x) => match x.pat.as_ref() {
+                Pat::Ident(x) => Some(x.ident.clone()),
+                _ => None,
+            },
+            _ => None,
+        }).collect();
+        if inputs.len() != method.sig.inputs.iter().filter(|x| matches!(x, FnArg::Typed(_))).count() {
+            continue;
+        }
+        let body = match parse_expr::get_ref_receiver(&method) {
+            Some(true) => {
+                let _super = parse_expr::get_super_mut(&ctx, 1);
+                quote!{ #_super . #method_name ( #(#inputs),* ) }
+            },
+            Some(false) => quote!{ self.__prototype__ . #method_name ( #(#inputs),* ) },
+            None if matches!(method.sig.inputs.first(), Some(FnArg::Receiver(_))) => continue,
+            None => quote!{ #parent_name :: #method_name ( #(#inputs),* ) },
+        };
+        let attrs = method.attrs.iter().filter(|x| !x.path.is_ident("class_method"));
+        let vis = &method.vis;
+        let sig = &method.sig;
+        info._impl.as_mut().unwrap().items.push(ImplItem::Method(syn::parse2(quote!{
+            #(#attrs)*
+            #vis #sig {
+                #body
+            }
+        }).unwrap()));
+    }
+}
+
 fn retrieve_implements_from_parent(info: &mut ClassInfo, parent: &ClassInfo) {
     let mro: Vec<Ident> = info.get_mro().iter().map(|ci| ci.get_real()).collect();
     for key in parent._trait_impl.keys() {
@@ -295,7 +1187,8 @@ fn create_real_trait(info: &mut ClassInfo) {
     let keep = String::from("keep");
     for method in &real_methods {
         let attrs = &method.attrs;
-        if get_meta_from_method(method).contains(&keep) {
+        let meta = get_meta_from_method(method);
+        if meta.contains(&keep) || meta.contains(&String::from("validate")) {
             continue;
         }
         let sig = &method.sig;
@@ -305,16 +1198,21 @@ fn create_real_trait(info: &mut ClassInfo) {
         }).unwrap());
     }
 
+    let thread_safe = if info.is_thread_safe() {
+        quote!{ Send + Sync }
+    } else {
+        quote!{}
+    };
     if info._parent.is_some() {
         let prototype = info.get_parent_info().get_real();
         info.real_trait = Some(syn::parse2(quote!{
-            pub trait #real : #prototype {
+            pub trait #real : #prototype + #thread_safe {
                 #(#trait_items)*
             }
         }).unwrap());
     } else {
         info.real_trait = Some(syn::parse2(quote!{
-            pub trait #real {
+            pub trait #real : #thread_safe {
                 #(#trait_items)*
             }
         }).unwrap());
@@ -346,6 +1244,8 @@ fn move_methods_to_prototype(info: &mut ClassInfo){
 fn move_methods_to_real(info: &mut ClassInfo){
     let real = info.get_real();
     let name = info.get_ident();
+    let ctx = ExprContext::new(info);
+    let is_static = info.is_static();
 
     let key = Box::new(real.clone());
     let mut o_real_impl = info._trait_impl.get_mut(&key);
@@ -371,16 +1271,54 @@ fn move_methods_to_real(info: &mut ClassInfo){
     });
     for method in methods {
         let attrs = get_meta_from_method(method);
-        parse_expr::parse_block(&mut method.block);
-        if attrs.contains(&keep) {
-            remove_meta_from_method(method, &keep);
+        if attrs.contains(&keep) || attrs.contains(&String::from("validate")) {
+            parse_expr::parse_method(method, &ctx);
             from.items.push(syn::ImplItem::Method(method.to_owned()))
         } else {
+            if is_static {
+                create_static_helper(&ctx, from, &real, method);
+            } else {
+                parse_expr::parse_method(method, &ctx);
+            }
             to.items.push(syn::ImplItem::Method(method.to_owned()));
         }
     }
 }
 
+/// the generic helper for <code>method</code> of <code>_trait</code> in class with <code>#\[static_dispatch\]</code>.
+fn get_static_helper(_trait: &Ident, method: &Ident) -> Ident {
+    let _trait = format_ident!("{}", _trait.to_string().trim_matches('_'));
+    format_ident!("__{}_{}_static__", to_snake_case(&_trait), method)
+}
+
+/// move the body of <code>method</code> to a generic helper in <code>_impl</code>,
+/// which use the parameter <code>\_\_real\_\_: *mut R</code> for <code>self</code>, so calls to a known type can be inlined.
+fn create_static_helper(ctx: &ExprContext, _impl: &mut ItemImpl, _trait: &Ident, method: &mut ImplItemMethod) {
+    let is_mut = match parse_expr::get_ref_receiver(method) {
+        Some(is_mut) if method.sig.generics.params.is_empty() => is_mut,
+        _ => {
+            parse_expr::parse_method(method, ctx);
+            return;
+        },
+    };
+    let real = format_ident!("__{}__", ctx.name);
+    let helper_ctx = ExprContext { static_real: true, ..ctx.clone() };
+    let mut helper = method.clone();
+    parse_expr::parse_method(&mut helper, &helper_ctx);
+    helper.sig.ident = get_static_helper(_trait, &method.sig.ident);
+    helper.sig.generics.params.insert(0, syn::parse_quote!{ __R__: ?Sized + #real });
+    helper.sig.inputs.insert(1, syn::parse_quote!{ __real__: *mut __R__ });
+    helper.attrs.push(syn::parse_quote!{ #[doc(hidden)] });
+    helper.vis = syn::parse_quote!{ pub };
+
+    let inputs = get_inputs_call(&method.sig.inputs.iter().cloned().collect::<Vec<FnArg>>());
+    let helper_ident = &helper.sig.ident;
+    let call = parse_expr::branch_on_real(is_mut, quote!{ self . #helper_ident ::<Self>( __real__, #(#inputs),* ) },
+        quote!{ self . #helper_ident ::<dyn #real>( __real__, #(#inputs),* ) });
+    method.block = syn::parse2(quote!{ { #call } }).unwrap();
+    _impl.items.push(ImplItem::Method(helper));
+}
+
 fn move_methods_to_impl(from: &mut ItemImpl, to: &mut ItemImpl, origin: &ItemImpl) {
     let real_methods: Vec<String> = get_methods(origin).iter()
             .map(get_signature_string).collect();
@@ -1,20 +1,105 @@
-use proc_macro2::TokenStream;
-use syn::{Expr, Block, Pat, Stmt, ExprTuple, Error};
+use std::cell::Cell;
+
+use proc_macro2::{TokenStream, Ident};
+use syn::{Expr, Block, Pat, Stmt, ExprTuple, Error, PathArguments, GenericArgument, Type, ImplItemMethod, FnArg};
 use quote::{quote, ToTokens};
 
-fn parse_statement(stmt: &mut Stmt) -> TokenStream {
+use crate::info::{ClassInfo, get_prototype_mut};
+
+/// the class which methods are converted for.
+#[derive(Clone)]
+pub struct ExprContext {
+    pub name: Ident,
+    /// ancestors from <code>get_mro</code>, the nearest parent first.
+    pub mro: Vec<Ident>,
+    /// from <code>ClassInfo::get_layouts</code>.
+    pub layouts: Vec<bool>,
+    /// convert <code>self</code> with the parameter <code>\_\_real\_\_: *mut R</code> of a static helper.
+    pub static_real: bool,
+    /// convert <code>self</code> with <code>Weak::upgrade</code> for class with <code>#\[rc\]</code> or <code>#\[arc\]</code>.
+    pub shared: bool,
+    /// whether <code>self</code> is used in the method of shared class being converted, so it is upgraded once at the start.
+    pub upgraded: Cell<bool>,
+    /// reject <code>self\_mut</code> in methods with <code>&self</code> for class with <code>#\[thread\_safe\]</code>.
+    pub thread_safe: bool,
+    /// from <code>ClassInfo::get_tracker</code>.
+    pub tracker: Option<Ident>,
+    /// the method being converted, for messages of borrow tracking.
+    pub method: Option<Ident>,
+    /// <code>Some(is_mut)</code> if the method being converted has <code>&self</code> or <code>&mut self</code>.
+    pub receiver: Option<bool>,
+}
+
+impl ExprContext {
+    pub fn new(info: &ClassInfo) -> Self {
+        ExprContext {
+            name: info.get_ident(),
+            mro: info.get_mro().iter().map(|x| x.get_ident()).collect(),
+            layouts: info.get_layouts(),
+            static_real: false,
+            shared: info.get_shared().is_some(),
+            upgraded: Cell::new(false),
+            thread_safe: info.is_thread_safe(),
+            tracker: info.get_tracker(),
+            method: None,
+            receiver: None,
+        }
+    }
+
+    /// how many <code>__prototype__</code> to go through to reach <code>ancestor</code>.
+    fn get_depth(&self, ancestor: &Ident) -> Option<usize> {
+        self.mro.iter().position(|x| x == ancestor).map(|x| x + 1)
+    }
+}
+
+/// <code>&mut</code> to the prototype at <code>depth</code> from <code>self</code>.
+pub fn get_super_mut(ctx: &ExprContext, depth: usize) -> TokenStream {
+    let prototype = get_prototype_mut(&ctx.layouts, depth, quote!{self});
+    let prototype = if ctx.layouts[depth - 1] {
+        quote!{ &mut #prototype }
+    } else {
+        prototype
+    };
+    if ctx.layouts[..depth].iter().all(|x| *x) {
+        quote!{ (#prototype) }
+    } else {
+        quote!{ unsafe { #prototype } }
+    }
+}
+
+fn get_super(arguments: &PathArguments, ctx: &ExprContext) -> Result<usize, Error> {
+    let args = match arguments {
+        PathArguments::None => return Ok(1),
+        PathArguments::AngleBracketed(args) => args,
+        PathArguments::Parenthesized(args) => return Err(Error::new_spanned(args, "expect an ancestor like `_super::<Parent>`")),
+    };
+    if args.args.len() != 1 {
+        return Err(Error::new_spanned(args, "expect exactly one ancestor"));
+    }
+    let ancestor = match &args.args[0] {
+        GenericArgument::Type(Type::Path(ty)) if ty.qself.is_none() => ty.path.get_ident(),
+        _ => None,
+    };
+    match ancestor {
+        Some(ancestor) => ctx.get_depth(ancestor).ok_or_else(|| Error::new_spanned(ancestor, 
+            format!("`{}` is not an ancestor of `{}`", ancestor, ctx.name))),
+        None => Err(Error::new_spanned(&args.args[0], "expect the name of an ancestor")),
+    }
+}
+
+fn parse_statement(stmt: &mut Stmt, ctx: &ExprContext) -> TokenStream {
     match stmt {
         Stmt::Local(x) => {
             if x.init.is_some() {
-                parse_expr(&mut x.init.as_mut().unwrap().1);
+                parse_expr(&mut x.init.as_mut().unwrap().1, ctx);
             }
         },
         Stmt::Item(_) => { },
         Stmt::Expr(x) => {
-            parse_expr(x);
+            parse_expr(x, ctx);
         },
         Stmt::Semi(x, _) => {
-            parse_expr(x);
+            parse_expr(x, ctx);
         },
     }
     stmt.to_token_stream()
@@ -24,121 +109,216 @@ fn parse_pattern(pat: &mut Pat) -> TokenStream {
     pat.to_token_stream()
 }
 
-pub fn parse_block(block: &mut Block) -> TokenStream {
+/// borrow <code>value</code> with the guard of <code>#\[track_borrows\]</code>,
+/// <code>mutable</code> is <code>None</code> for the frame of a method with <code>&self</code>.
+fn create_borrow(ctx: &ExprContext, value: TokenStream, mutable: Option<bool>) -> Option<TokenStream> {
+    let tracker = ctx.tracker.as_ref()?;
+    let prototypes = vec![quote!{__prototype__}; ctx.mro.len()];
+    let name = match &ctx.method {
+        Some(method) => format!("{}::{}", ctx.name, method),
+        None => ctx.name.to_string(),
+    };
+    let mutable = match mutable {
+        Some(mutable) => quote!{ Some(#mutable) },
+        None => quote!{ None },
+    };
+    Some(quote!{
+        #tracker::new(#value, &self #(.#prototypes)* .__borrow__, #mutable, #name)
+    })
+}
+
+/// pointer to the most derived object for <code>self</code> or <code>self\_mut</code>.
+/// 
+/// if the receiver is the most derived object itself, the pointer is derived from the receiver instead of <code>\_\_real\_\_</code>,
+/// so the receiver is not invalidated by an access through an older pointer.
+fn get_real_ptr(ctx: &ExprContext, mutable: bool) -> TokenStream {
+    if ctx.static_real {
+        return quote!{ __real__ };
+    }
+    let real = quote::format_ident!("__{}__", ctx.name);
+    let is_mut = match ctx.receiver {
+        Some(true) => true,
+        Some(false) if !mutable => false,
+        _ => return get_checked_real(),
+    };
+    branch_on_real(is_mut, quote!{ __real__ as *mut dyn #real }, quote!{ __real__ })
+}
+
+/// <code>then</code> if the receiver is the most derived object, otherwise <code>otherwise</code>,
+/// both of them use <code>\_\_real\_\_</code>, which is <code>*mut Self</code> from the receiver or the checked <code>self.\_\_real\_\_</code>.
+pub fn branch_on_real(is_mut: bool, then: TokenStream, otherwise: TokenStream) -> TokenStream {
+    let this = if is_mut {
+        quote!{ self as *mut Self }
+    } else {
+        quote!{ self as *const Self as *mut Self }
+    };
+    let checked = get_checked_real();
+    // only addresses are compared, an inline prototype is never at the start of the object containing it.
+    quote!{
+        {
+            let __real__ = #this;
+            if ::core::ptr::addr_eq(self.__real__, __real__) {
+                #then
+            } else {
+                let __real__ = #checked;
+                #otherwise
+            }
+        }
+    }
+}
+
+/// <code>Some(is_mut)</code> if <code>method</code> has <code>&self</code> or <code>&mut self</code>.
+pub fn get_ref_receiver(method: &ImplItemMethod) -> Option<bool> {
+    match method.sig.inputs.first() {
+        Some(FnArg::Receiver(x)) if x.reference.is_some() => Some(x.mutability.is_some()),
+        _ => None,
+    }
+}
+
+/// <code>self.\_\_real\_\_</code>, which panic if it is null, so an object not created by its constructor is never dereferenced.
+pub fn get_checked_real() -> TokenStream {
+    quote!{
+        {
+            let __real__ = self.__real__;
+            if __real__.is_null() {
+                panic!("`__real__` is null, the object should be created by its constructor");
+            }
+            __real__
+        }
+    }
+}
+
+/// convert the body of <code>method</code>, and track the borrow of its <code>&self</code> if needed.
+pub fn parse_method(method: &mut ImplItemMethod, ctx: &ExprContext) {
+    let receiver = get_ref_receiver(method);
+    let ctx = ExprContext { method: Some(method.sig.ident.clone()), receiver, upgraded: Cell::new(false), ..ctx.clone() };
+    parse_block(&mut method.block, &ctx);
+    if ctx.upgraded.get() {
+        method.block.stmts.insert(0, syn::parse2(quote!{ let __self__ = &*self.__real__.upgrade().unwrap(); }).unwrap());
+    }
+    if receiver != Some(false) || ctx.shared {
+        return;
+    }
+    if let Some(frame) = create_borrow(&ctx, quote!{ ::core::ptr::null_mut::<()>() }, None) {
+        method.block.stmts.insert(0, syn::parse2(quote!{ let __frame__ = #frame; }).unwrap());
+    }
+}
+
+pub fn parse_block(block: &mut Block, ctx: &ExprContext) -> TokenStream {
     for line in &mut block.stmts {
-        parse_statement(line);
+        parse_statement(line, ctx);
     }
     block.to_token_stream()
 }
 
-pub fn parse_expr(expr: &mut Expr) -> TokenStream{
+pub fn parse_expr(expr: &mut Expr, ctx: &ExprContext) -> TokenStream{
     
     match expr {
         Expr::Array(x) => {
             for elem in &mut x.elems {
-                parse_expr(elem);
+                parse_expr(elem, ctx);
             }
         },
         Expr::Assign(x) => { 
-            parse_expr(&mut x.left);
-            parse_expr(&mut x.right); 
+            parse_expr(&mut x.left, ctx);
+            parse_expr(&mut x.right, ctx); 
         },
         Expr::AssignOp(x) => { 
-            parse_expr(&mut x.left);
-            parse_expr(&mut x.right); 
+            parse_expr(&mut x.left, ctx);
+            parse_expr(&mut x.right, ctx); 
         },
         Expr::Async(x) => { 
             for stmt in &mut x.block.stmts {
-                parse_statement(stmt);
+                parse_statement(stmt, ctx);
             }
         },
         Expr::Await(x) => {
-            parse_expr(&mut x.base);
+            parse_expr(&mut x.base, ctx);
         },
         Expr::Binary(x) => {
-            parse_expr(&mut x.left);
-            parse_expr(&mut x.right);
+            parse_expr(&mut x.left, ctx);
+            parse_expr(&mut x.right, ctx);
         },
         Expr::Block(x) => {
             for stmt in &mut x.block.stmts {
-                parse_statement(stmt);
+                parse_statement(stmt, ctx);
             }
         },
         Expr::Box(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
         },
         Expr::Break(_) => {},
         Expr::Call(x) => {
             for arg in &mut x.args {
-                parse_expr(arg);
+                parse_expr(arg, ctx);
             }
-            parse_expr(&mut x.func);
+            parse_expr(&mut x.func, ctx);
         },
         Expr::Cast(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
         },
         Expr::Closure(x) => {
-            parse_expr(&mut x.body);
+            parse_expr(&mut x.body, ctx);
         },
         Expr::Continue(_) => {},
         Expr::Field(x) => {
-            x.base = syn::parse2(parse_expr(&mut x.base)).unwrap();
+            x.base = syn::parse2(parse_expr(&mut x.base, ctx)).unwrap();
         },
         Expr::ForLoop(x) => {
             for line in &mut x.body.stmts {
-                parse_statement(line);
+                parse_statement(line, ctx);
             }
         },
         Expr::Group(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
         },
         Expr::If(x) => {
-            parse_expr(&mut x.cond);
-            parse_block(&mut x.then_branch);
+            parse_expr(&mut x.cond, ctx);
+            parse_block(&mut x.then_branch, ctx);
             if x.else_branch.is_some() {
-                parse_expr(&mut x.else_branch.as_mut().unwrap().1);
+                parse_expr(&mut x.else_branch.as_mut().unwrap().1, ctx);
             }
         },
         Expr::Index(x) => {
-            x.expr = syn::parse2(parse_expr(&mut x.expr)).unwrap();
+            x.expr = syn::parse2(parse_expr(&mut x.expr, ctx)).unwrap();
             
-            parse_expr(&mut x.index);
+            parse_expr(&mut x.index, ctx);
         },
         Expr::Let(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
         },
         Expr::Lit(_) => {},
         Expr::Loop(x) => {
-            parse_block(&mut x.body);
+            parse_block(&mut x.body, ctx);
         },
         Expr::Macro(x) => {
             let tokens = &x.mac.tokens;
             
             let _expr: Result<ExprTuple, Error> = syn::parse2(quote!{( #tokens )});
             if _expr.is_ok() {
-                let _expr: Result<ExprTuple, Error> = syn::parse2(parse_expr(&mut Expr::Tuple(_expr.unwrap().to_owned())));
+                let _expr: Result<ExprTuple, Error> = syn::parse2(parse_expr(&mut Expr::Tuple(_expr.unwrap().to_owned()), ctx));
                 x.mac.tokens = _expr.unwrap().elems.to_token_stream();
             } else {
                 let _expr: &mut Expr = &mut syn::parse2(tokens.clone()).unwrap();
-                let _expr: Expr = syn::parse2(parse_expr(_expr)).unwrap();
+                let _expr: Expr = syn::parse2(parse_expr(_expr, ctx)).unwrap();
                 x.mac.tokens = _expr.to_token_stream();
             }
         },
         Expr::Match(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
             for arm in &mut x.arms {
                 parse_pattern(&mut arm.pat);
-                parse_expr(&mut arm.body);
+                parse_expr(&mut arm.body, ctx);
             }
         },
         Expr::MethodCall(method) => {
-            method.receiver = syn::parse2(parse_expr(&mut method.receiver)).unwrap();
+            method.receiver = syn::parse2(parse_expr(&mut method.receiver, ctx)).unwrap();
             for arg in &mut method.args {
-                parse_expr(arg);
+                parse_expr(arg, ctx);
             }
         },
         Expr::Paren(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
         },
         Expr::Path(x) => {
             let segments = &mut x.path.segments;
@@ -146,71 +326,93 @@ pub fn parse_expr(expr: &mut Expr) -> TokenStream{
                 let ident = segments[0].ident.to_string();
                 if &ident == "this" {
                     return quote!{self}
+                } else if &ident == "self" && ctx.shared {
+                    ctx.upgraded.set(true);
+                    return quote!{__self__}
                 } else if &ident == "self" {
-                    return quote!{unsafe {self.__real__.as_ref().unwrap()}}
+                    let real = get_real_ptr(ctx, false);
+                    if let Some(borrow) = create_borrow(ctx, real.clone(), Some(false)) {
+                        return quote!{(&*#borrow)}
+                    }
+                    return quote!{unsafe { &*#real }}
+                } else if &ident == "self_mut" && ctx.shared {
+                    return Error::new_spanned(x, format!("`self_mut` is not available for shared {}, use interior mutability instead", ctx.name)).to_compile_error()
+                } else if &ident == "self_mut" && ctx.thread_safe && ctx.receiver == Some(false) {
+                    let method = ctx.method.as_ref().unwrap();
+                    return Error::new_spanned(x, format!("`self_mut` is not available in {} of thread safe {} with `&self`, use `&mut self` or interior mutability instead", method, ctx.name)).to_compile_error()
                 } else if &ident == "self_mut" {
-                    return quote!{unsafe { self.__real__.as_mut().unwrap() }}
-                } else if &ident == "_super" {
-                    return quote!{self.__prototype__}
-                } else if &ident == "_super_mut" {
-                    return quote!{unsafe { self.__prototype__.as_mut().get_unchecked_mut() }}
+                    let real = get_real_ptr(ctx, true);
+                    if let Some(borrow) = create_borrow(ctx, real.clone(), Some(true)) {
+                        return quote!{(&mut *#borrow)}
+                    }
+                    return quote!{unsafe { &mut *#real }}
+                } else if &ident == "_super" || &ident == "_super_mut" {
+                    let depth = match get_super(&segments[0].arguments, ctx) {
+                        Ok(depth) => depth,
+                        Err(err) => return err.to_compile_error(),
+                    };
+                    if &ident == "_super" {
+                        let prototypes = vec![quote!{__prototype__}; depth];
+                        return quote!{self #(.#prototypes)*}
+                    }
+                    return get_super_mut(ctx, depth)
                 }
             }
         },
         Expr::Range(x) => {
             if x.from.is_some() {
-                parse_expr(&mut x.from.as_mut().unwrap());
+                parse_expr(&mut x.from.as_mut().unwrap(), ctx);
             }
             if x.to.is_some() {
-                parse_expr(&mut x.to.as_mut().unwrap());
+                parse_expr(&mut x.to.as_mut().unwrap(), ctx);
             }
         },
         Expr::Reference(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
         },
         Expr::Repeat(x) => {
-            parse_expr(&mut x.expr);
-            parse_expr(&mut x.len);
+            parse_expr(&mut x.expr, ctx);
+            parse_expr(&mut x.len, ctx);
         },
         Expr::Return(x) => {
             if x.expr.is_some() {
-                parse_expr(&mut x.expr.as_mut().unwrap());
+                parse_expr(&mut x.expr.as_mut().unwrap(), ctx);
             }
         },
         Expr::Struct(x) => {
             for field in &mut x.fields {
-                parse_expr(&mut field.expr);
+                parse_expr(&mut field.expr, ctx);
             }
             if x.rest.is_some() {
-                parse_expr(&mut x.rest.as_mut().unwrap());
+                parse_expr(&mut x.rest.as_mut().unwrap(), ctx);
             }
         },
         Expr::Try(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
         },
         Expr::TryBlock(x) => {
-            parse_block(&mut x.block);
+            parse_block(&mut x.block, ctx);
         },
         Expr::Tuple(x) => {
             for elem in &mut x.elems {
-                parse_expr(elem);
+                parse_expr(elem, ctx);
             }
         },
         Expr::Type(_) => {},
         Expr::Unary(x) => {
-            parse_expr(&mut x.expr);
+            parse_expr(&mut x.expr, ctx);
         },
         Expr::Unsafe(x) => {
-            parse_block(&mut x.block);
+            parse_block(&mut x.block, ctx);
         },
         Expr::Verbatim(_) => {},
         Expr::While(x) => {
-            parse_expr(&mut x.cond);
-            parse_block(&mut x.body);
+            parse_expr(&mut x.cond, ctx);
+            parse_block(&mut x.body, ctx);
         },
         Expr::Yield(x) => {
             if x.expr.is_some() {
-                parse_expr(&mut x.expr.as_mut().unwrap());
+                parse_expr(&mut x.expr.as_mut().unwrap(), ctx);
             }
         },
         _ => {
@@ -0,0 +1,432 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, format_ident, ToTokens};
+use syn::{FnArg, ImplItem, ImplItemMethod};
+
+use crate::info::ClassInfo;
+
+/// tokens of a type or signature without spaces inside, like <code>Vec<String></code> instead of <code>Vec < String ></code>.
+fn to_compact_string(tokens: impl ToTokens) -> String {
+    tokens.to_token_stream().to_string()
+        .replace(" :: ", "::").replace(":: ", "::")
+        .replace(" <", "<").replace("< ", "<").replace(" >", ">")
+        .replace(" ,", ",").replace("& ", "&").replace(" : ", ": ")
+        .replace("( ", "(").replace(" )", ")").replace("[ ", "[").replace(" ]", "]").replace(" ;", ";")
+}
+
+/// create <code>META</code> of class whose root class has <code>#\[meta\]</code>,
+/// which describes fields and methods with <code>self</code> declared or overridden by the class, ancestors are reached by <code>parent</code>,
+/// and add <code>class\_meta</code> to its methods.
+pub fn create_meta(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    if info.has_attr("meta") && info._parent.is_some() && !info.get_parent_info().inherits_attr("meta") {
+        let err = syn::Error::new_spanned(&name, format!("`#[meta]` of {} should be used on the root class", name)).to_compile_error();
+        info.extra_items.push(syn::parse2(err).unwrap());
+        return;
+    }
+    if !info.inherits_attr("meta") {
+        return;
+    }
+    let class_name = name.to_string();
+    let parent = match &info._parent {
+        Some(parent) => {
+            let parent = &parent.parent;
+            quote!{ ::core::option::Option::Some(&#parent::META) }
+        },
+        None => quote!{ ::core::option::Option::None },
+    };
+    let fields: Vec<TokenStream> = info.get_fields().iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap().to_string();
+        let type_name = to_compact_string(&field.ty);
+        quote!{ FieldMeta { name: #field_name, type_name: #type_name, class: #class_name } }
+    }).collect();
+    let methods: Vec<TokenStream> = info._impl.as_ref().unwrap().items.iter().filter_map(|item| match item {
+        ImplItem::Method(x) if matches!(x.sig.inputs.first(), Some(FnArg::Receiver(_))) => Some(x),
+        _ => None,
+    }).map(|method| {
+        let method_name = method.sig.ident.to_string();
+        let signature = to_compact_string(&method.sig).replacen(&format!("{} (", method_name), &format!("{}(", method_name), 1);
+        quote!{ MethodMeta { name: #method_name, signature: #signature, class: #class_name } }
+    }).collect();
+
+    let items = [
+        quote!{
+            /// fields and methods of this class.
+            pub const META: ClassMeta = ClassMeta {
+                name: #class_name,
+                parent: #parent,
+                fields: &[#(#fields),*],
+                methods: &[#(#methods),*],
+            };
+        },
+        quote!{
+            fn class_meta(&self) -> &'static ClassMeta {
+                &Self::META
+            }
+        },
+    ];
+    info.add_items(items.into_iter().map(|x| syn::parse2(x).unwrap()));
+}
+
+/// create <code>invoke</code>, <code>get\_field</code> and <code>set\_field</code> for methods and fields with <code>#\[reflect\]</code>
+/// of class whose root class has <code>#\[meta\]</code>, members not found in the class are looked up in its prototype.
+pub fn create_invoke(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    let mut methods: Vec<syn::Signature> = Vec::new();
+    for item in &mut info._impl.as_mut().unwrap().items {
+        if let ImplItem::Method(method) = item {
+            if method.attrs.iter().any(|x| x.path.is_ident("reflect")) {
+                method.attrs.retain(|x| !x.path.is_ident("reflect"));
+                methods.push(method.sig.clone());
+            }
+        }
+    }
+    let fields: Vec<syn::Field> = info.get_fields().into_iter()
+        .filter(|x| x.attrs.iter().any(|x| x.path.is_ident("reflect"))).collect();
+    if !info.inherits_attr("meta") {
+        if !methods.is_empty() || !fields.is_empty() {
+            let err = syn::Error::new_spanned(&name, format!("`#[reflect]` of {} requires `#[meta]` on the root class", name)).to_compile_error();
+            info.extra_items.push(syn::parse2(err).unwrap());
+        }
+        return;
+    }
+    let shared = info.get_shared().is_some();
+    let has_parent = info._parent.is_some();
+
+    let mut method_arms: Vec<TokenStream> = Vec::new();
+    for sig in &methods {
+        let method = &sig.ident;
+        let method_name = method.to_string();
+        let receiver = match sig.inputs.first() {
+            Some(FnArg::Receiver(x)) if x.mutability.is_some() && !shared => quote!{ self_mut },
+            Some(FnArg::Receiver(_)) => quote!{ self },
+            _ => {
+                let err = syn::Error::new_spanned(sig, format!("`#[reflect]` method {} of {} should have `self`", method, name)).to_compile_error();
+                info.extra_items.push(syn::parse2(err).unwrap());
+                continue;
+            },
+        };
+        let types: Vec<&syn::Type> = sig.inputs.iter().filter_map(|x| match x {
+            FnArg::Typed(x) => Some(x.ty.as_ref()),
+            _ => None,
+        }).collect();
+        let len = types.len();
+        let indexes: Vec<usize> = (0..len).collect();
+        let args: Vec<proc_macro2::Ident> = (0..len).map(|x| quote::format_ident!("__arg{}__", x)).collect();
+        method_arms.push(quote!{
+            #method_name => {
+                if args.len() != #len {
+                    return ::core::result::Result::Err(ReflectError::WrongArity { name: #method_name, expected: #len, found: args.len() });
+                }
+                #(
+                    let #args = match <#types as ReflectValue>::from_value(&args[#indexes]) {
+                        ::core::option::Option::Some(x) => x,
+                        ::core::option::Option::None => return ::core::result::Result::Err(ReflectError::TypeMismatch { name: #method_name, index: #indexes, expected: <#types as ReflectValue>::TYPE_NAME }),
+                    };
+                )*
+                ::core::result::Result::Ok(ReflectValue::into_value(#receiver.#method(#(#args),*)))
+            },
+        });
+    }
+    let mut get_arms: Vec<TokenStream> = Vec::new();
+    let mut set_arms: Vec<TokenStream> = Vec::new();
+    for field in &fields {
+        let id = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let field_name = id.to_string();
+        get_arms.push(quote!{
+            #field_name => ::core::result::Result::Ok(ReflectValue::into_value(::core::clone::Clone::clone(&this.#id))),
+        });
+        set_arms.push(quote!{
+            #field_name => {
+                this.#id = match <#ty as ReflectValue>::from_value(&value) {
+                    ::core::option::Option::Some(x) => x,
+                    ::core::option::Option::None => return ::core::result::Result::Err(ReflectError::TypeMismatch { name: #field_name, index: 0, expected: <#ty as ReflectValue>::TYPE_NAME }),
+                };
+                ::core::result::Result::Ok(())
+            },
+        });
+    }
+    let alloc = info.get_alloc();
+    let unknown = quote!{ ::core::result::Result::Err(ReflectError::UnknownMember(#alloc::string::String::from(name))) };
+    let (invoke_rest, get_rest, set_rest) = if has_parent {
+        (quote!{ _super_mut.invoke(name, args) }, quote!{ _super.get_field(name) }, quote!{ _super_mut.set_field(name, value) })
+    } else {
+        (unknown.clone(), unknown.clone(), unknown)
+    };
+    let items = [
+        quote!{
+            #[allow(unused_variables)]
+            fn invoke(&mut self, name: &str, args: &[Value]) -> ::core::result::Result<Value, ReflectError> {
+                match name {
+                    #(#method_arms)*
+                    _ => #invoke_rest,
+                }
+            }
+        },
+        quote!{
+            #[allow(unused_variables)]
+            fn get_field(&self, name: &str) -> ::core::result::Result<Value, ReflectError> {
+                match name {
+                    #(#get_arms)*
+                    _ => #get_rest,
+                }
+            }
+        },
+        quote!{
+            #[allow(unused_variables)]
+            fn set_field(&mut self, name: &str, value: Value) -> ::core::result::Result<(), ReflectError> {
+                match name {
+                    #(#set_arms)*
+                    _ => #set_rest,
+                }
+            }
+        },
+    ];
+    info.add_items(items.into_iter().map(|x| syn::parse2(x).unwrap()));
+}
+
+/// the attribute of methods generated by <code>create\_reflection</code>, which is removed from the output.
+pub const GENERATED: &str = "__generated__";
+
+/// the method named <code>name</code> declared by the class, in its impl or its implementations of traits.
+fn find_declared_method(info: &ClassInfo, name: &str) -> Option<ImplItemMethod> {
+    let impls = info._impl.iter().chain(info._trait_impl.values().map(|x| x.as_ref()));
+    impls.flat_map(|x| x.items.iter()).find_map(|item| match item {
+        ImplItem::Method(x) if x.sig.ident == name => Some(x.clone()),
+        _ => None,
+    })
+}
+
+/// whether <code>item</code> can be added to the class, that is the class does not declare the name,
+/// and a method is not declared by the user in the nearest ancestor declaring it.
+fn can_add_item(info: &ClassInfo, item: &ImplItem) -> bool {
+    let name = match item {
+        ImplItem::Const(x) => &x.ident,
+        ImplItem::Method(x) => &x.sig.ident,
+        _ => return true,
+    };
+    let declared = info._impl.as_ref().unwrap().items.iter().any(|item| match item {
+        ImplItem::Const(x) => &x.ident == name,
+        ImplItem::Method(x) => &x.sig.ident == name,
+        _ => false,
+    });
+    if declared {
+        return false;
+    }
+    if !matches!(item, ImplItem::Method(_)) {
+        return true;
+    }
+    match info.get_mro().iter().find_map(|x| find_declared_method(x, &name.to_string())) {
+        Some(method) => method.attrs.iter().any(|x| x.path.is_ident(GENERATED)),
+        None => true,
+    }
+}
+
+/// add <code>CLASS_NAME</code>, <code>PARENT_CLASS_NAME</code> and <code>MRO</code> to the class,
+/// and <code>class_name</code>, <code>parent_class_name</code> and <code>mro</code> to its methods.
+/// 
+/// a member is skipped if the user declares the name in the class, or declares the method in an ancestor.
+pub fn create_reflection(info: &mut ClassInfo) {
+    let name = info.get_ident().to_string();
+    let mro: Vec<String> = info.get_mro().iter().map(|x| x.get_ident().to_string()).collect();
+    let parent = match mro.first() {
+        Some(parent) => quote!{ ::core::option::Option::Some(#parent) },
+        None => quote!{ ::core::option::Option::None },
+    };
+    let items = [
+        quote!{
+            /// name of this class.
+            pub const CLASS_NAME: &'static str = #name;
+        },
+        quote!{
+            /// name of the parent of this class.
+            pub const PARENT_CLASS_NAME: ::core::option::Option<&'static str> = #parent;
+        },
+        quote!{
+            /// names of this class and its ancestors, from this class to the root class.
+            pub const MRO: &'static [&'static str] = &[#name #(, #mro)*];
+        },
+        quote!{
+            /// name of the most derived class.
+            #[__generated__]
+            fn class_name(&self) -> &'static str {
+                Self::CLASS_NAME
+            }
+        },
+        quote!{
+            /// name of the parent of the most derived class.
+            #[__generated__]
+            fn parent_class_name(&self) -> ::core::option::Option<&'static str> {
+                Self::PARENT_CLASS_NAME
+            }
+        },
+        quote!{
+            /// names of the most derived class and its ancestors.
+            #[__generated__]
+            fn mro(&self) -> &'static [&'static str] {
+                Self::MRO
+            }
+        },
+    ];
+    let items: Vec<ImplItem> = items.into_iter().map(|x| syn::parse2(x).unwrap())
+        .filter(|x| can_add_item(info, x)).collect();
+    info.add_items(items);
+}
+
+/// static methods with <code>#\[class\_method\]</code> of a class.
+fn get_class_methods(info: &ClassInfo) -> Vec<ImplItemMethod> {
+    info._impl.as_ref().unwrap().items.iter().filter_map(|item| match item {
+        ImplItem::Method(x) if x.attrs.iter().any(|x| x.path.is_ident("class_method")) => Some(x.clone()),
+        _ => None,
+    }).collect()
+}
+
+/// whether <code>Self</code> is used in the tokens.
+pub fn uses_self_type(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|x| match x {
+        TokenTree::Ident(x) => x == "Self",
+        TokenTree::Group(x) => uses_self_type(x.stream()),
+        _ => false,
+    })
+}
+
+/// create metaclass <code>XXXClass</code> and trait <code>\_\_XXXClass\_\_</code> for methods with <code>#\[class\_method\]</code>
+/// if the class or an ancestor has any, the trait extends the one of the parent and <code>XXXClass</code> implements all of them with the nearest class methods,
+/// so a class method called through <code>&dyn \_\_RootClass\_\_</code> is the one of the most derived class.
+/// 
+/// class methods are kept as associated functions, <code>class()</code> returns the metaclass,
+/// and <code>class(&self)</code> is added to its methods.
+pub fn create_metaclass(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    let generic = !info._impl.as_ref().unwrap().generics.params.is_empty();
+    let mut errors: Vec<syn::Error> = Vec::new();
+    for item in &mut info._impl.as_mut().unwrap().items {
+        if let ImplItem::Method(method) = item {
+            if !method.attrs.iter().any(|x| x.path.is_ident("class_method")) {
+                continue;
+            }
+            if matches!(method.sig.inputs.first(), Some(FnArg::Receiver(_))) {
+                errors.push(syn::Error::new_spanned(&method.sig, format!("`#[class_method]` {} of {} should not have `self`", method.sig.ident, name)));
+            } else if uses_self_type(method.sig.to_token_stream()) {
+                errors.push(syn::Error::new_spanned(&method.sig, format!("`#[class_method]` {} of {} should not use `Self` in its signature", method.sig.ident, name)));
+            } else if generic {
+                errors.push(syn::Error::new_spanned(&method.sig, format!("`#[class_method]` is not supported by generic class {}", name)));
+            }
+            method.attrs.push(syn::parse_quote!{ #[keep] });
+        }
+    }
+    if !errors.is_empty() {
+        for err in errors {
+            info.extra_items.push(syn::parse2(err.to_compile_error()).unwrap());
+        }
+        return;
+    }
+    if generic {
+        return;
+    }
+
+    let vis = info._struct.as_ref().unwrap().vis.clone();
+    let mut chain = vec![info.clone()];
+    chain.append(&mut info.get_mro());
+    let mut class_methods: Vec<Vec<ImplItemMethod>> = chain.iter().map(get_class_methods).collect();
+    // the chain starts from the farthest class with class methods, there is no metaclass without them.
+    match class_methods.iter().rposition(|x| !x.is_empty()) {
+        Some(top) => {
+            chain.truncate(top + 1);
+            class_methods.truncate(top + 1);
+        },
+        None => return,
+    }
+    // class methods declared by each class in the chain, not overriding the ones of its ancestors.
+    let declared: Vec<Vec<&ImplItemMethod>> = class_methods.iter().enumerate().map(|(i, methods)| {
+        methods.iter().filter(|method| {
+            !class_methods[i + 1..].iter().flatten().any(|x| x.sig.ident == method.sig.ident)
+        }).collect()
+    }).collect();
+    let get_trait = |class: &ClassInfo| format_ident!("__{}Class__", class.get_ident());
+    let get_trait_sig = |method: &ImplItemMethod| {
+        let mut sig = method.sig.clone();
+        sig.inputs.insert(0, syn::parse_quote!{ &self });
+        sig
+    };
+
+    let metaclass = format_ident!("{}Class", name);
+    let _trait = get_trait(info);
+    let root_trait = get_trait(chain.last().unwrap());
+    let trait_items: Vec<syn::Signature> = declared[0].iter().map(|x| get_trait_sig(x)).collect();
+    let trait_items = if chain.len() > 1 {
+        let parent_trait = get_trait(&chain[1]);
+        quote!{
+            pub trait #_trait : #parent_trait {
+                #(#trait_items;)*
+            }
+        }
+    } else {
+        quote!{
+            pub trait #_trait {
+                /// name of the class.
+                fn class_name(&self) -> &'static str;
+                #(#trait_items;)*
+            }
+        }
+    };
+
+    let mut items = vec![
+        quote!{
+            /// metaclass of this class, whose methods call the class methods.
+            #vis struct #metaclass;
+        },
+        trait_items,
+    ];
+    for (i, class) in chain.iter().enumerate() {
+        let class_trait = get_trait(class);
+        let mut methods: Vec<TokenStream> = Vec::new();
+        if i == chain.len() - 1 {
+            methods.push(quote!{
+                fn class_name(&self) -> &'static str {
+                    #name::CLASS_NAME
+                }
+            });
+        }
+        for method in &declared[i] {
+            let method_name = &method.sig.ident;
+            let owner = chain.iter().zip(&class_methods)
+                .find(|(_, methods)| methods.iter().any(|x| &x.sig.ident == method_name))
+                .map(|(x, _)| x.get_ident()).unwrap();
+            let args: Vec<TokenStream> = method.sig.inputs.iter().filter_map(|x| match x {
+                FnArg::Typed(x) => Some(x.pat.to_token_stream()),
+                _ => None,
+            }).collect();
+            let sig = get_trait_sig(method);
+            methods.push(quote!{
+                #sig {
+                    #owner::#method_name(#(#args),*)
+                }
+            });
+        }
+        items.push(quote!{
+            impl #class_trait for #metaclass {
+                #(#methods)*
+            }
+        });
+    }
+    for item in items {
+        info.extra_items.push(syn::parse2(item).unwrap());
+    }
+
+    let methods = [
+        quote!{
+            /// the metaclass of this class.
+            #[keep]
+            #vis fn class() -> &'static #metaclass {
+                &#metaclass
+            }
+        },
+        quote!{
+            fn class(&self) -> &'static dyn #root_trait {
+                &#metaclass
+            }
+        },
+    ];
+    info.add_items(methods.into_iter().map(|x| syn::parse2(x).unwrap()));
+}
@@ -0,0 +1,307 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, format_ident};
+
+use crate::{builder::get_new_inputs, info::ClassInfo, parse_class::find_method};
+
+/// types which implement <code>ReflectValue</code>.
+const VALUE_TYPES: [&str; 9] = ["()", "bool", "i32", "i64", "u32", "u64", "f32", "f64", "String"];
+
+fn is_value_type(ty: &syn::Type) -> bool {
+    let name = match ty {
+        syn::Type::Tuple(x) if x.elems.is_empty() => String::from("()"),
+        syn::Type::Path(x) if x.qself.is_none() => x.path.segments.last().unwrap().ident.to_string(),
+        _ => return false,
+    };
+    VALUE_TYPES.contains(&name.as_str())
+}
+
+fn is_generic(class: &ClassInfo) -> bool {
+    !class._impl.as_ref().unwrap().generics.params.is_empty()
+}
+
+/// the pointer to <code>dyn \_\_XXX\_\_</code> of <code>class</code> created by the registry.
+fn get_dyn_pointer(class: &ClassInfo) -> TokenStream {
+    let real = class.get_real();
+    match class.get_shared() {
+        Some((rc, _)) => quote!{ #rc<dyn #real> },
+        None => {
+            let _box = class.get_box();
+            quote!{ ::core::pin::Pin<#_box<dyn #real>> }
+        },
+    }
+}
+
+/// the function which calls <code>new</code> of <code>class</code> with <code>Value</code> as arguments,
+/// or <code>try\_new</code> if the class has validators.
+///
+/// there is no factory if <code>new</code> is missing or any parameter is not a type of <code>Value</code>.
+fn create_factory(class: &ClassInfo) -> Option<TokenStream> {
+    let inputs = get_new_inputs(class)?;
+    if !inputs.iter().all(|(_, ty)| is_value_type(ty)) {
+        return None;
+    }
+    let name = class.get_ident();
+    let pointer = match class.get_shared() {
+        Some((rc, _)) => quote!{ #rc<#name> },
+        None => {
+            let _box = class.get_box();
+            quote!{ ::core::pin::Pin<#_box<#name>> }
+        },
+    };
+    let alloc = class.get_alloc();
+    let class_name = name.to_string();
+    let len = inputs.len();
+    let indexes: Vec<usize> = (0..len).collect();
+    // `String` is not in the prelude of `no_std`.
+    let (args, types): (Vec<Ident>, Vec<TokenStream>) = inputs.into_iter().map(|(arg, ty)| match &ty {
+        syn::Type::Path(x) if x.path.is_ident("String") => (arg, quote!{ #alloc::string::String }),
+        _ => (arg, quote!{ #ty }),
+    }).unzip();
+    let create = match find_method(class, "try_new") {
+        Some(_) => quote!{
+            #name::try_new(#(#args),*).map_err(|err| ReflectError::Invalid { name: #class_name, error: #alloc::format!("{:?}", err) })
+        },
+        None => quote!{ ::core::result::Result::Ok(#name::new(#(#args),*)) },
+    };
+    Some(quote!{
+        fn create(args: &[Value]) -> ::core::result::Result<#pointer, ReflectError> {
+            if args.len() != #len {
+                return ::core::result::Result::Err(ReflectError::WrongArity { name: #class_name, expected: #len, found: args.len() });
+            }
+            #(
+                let #args = match <#types as ReflectValue>::from_value(&args[#indexes]) {
+                    ::core::option::Option::Some(x) => x,
+                    ::core::option::Option::None => return ::core::result::Result::Err(ReflectError::TypeMismatch { name: #class_name, index: #indexes, expected: <#types as ReflectValue>::TYPE_NAME }),
+                };
+            )*
+            #create
+        }
+    })
+}
+
+/// register class whose root class has <code>#\[register\]</code> when the program starts.
+///
+/// the class gets <code>\_\_ENTRY\_\_</code>, <code>dyn \_\_XXX\_\_</code> implements <code>registry::Registered</code>,
+/// and a function in <code>.init\_array</code> or its counterpart pushes the entry to <code>registry::classes()</code>
+/// and the factory to the factories of the class and every ancestor.
+pub fn create_registration(info: &mut ClassInfo) {
+    let name = info.get_ident();
+    if info.has_attr("register") && info._parent.is_some() && !info.get_parent_info().inherits_attr("register") {
+        let err = syn::Error::new_spanned(&name, format!("`#[register]` of {} should be used on the root class", name)).to_compile_error();
+        info.extra_items.push(syn::parse2(err).unwrap());
+        return;
+    }
+    if !info.inherits_attr("register") || is_generic(info) {
+        return;
+    }
+    let class_name = name.to_string();
+    let real = info.get_real();
+    let pointer = get_dyn_pointer(info);
+    let parent = match &info._parent {
+        Some(parent) if !is_generic(&info.get_parent_info()) => {
+            let parent = &parent.parent;
+            quote!{ ::core::option::Option::Some(#parent::__ENTRY__) }
+        },
+        _ => quote!{ ::core::option::Option::None },
+    };
+    let meta = if info.inherits_attr("meta") {
+        quote!{ ::core::option::Option::Some(&#name::META) }
+    } else {
+        quote!{ ::core::option::Option::None }
+    };
+
+    let mut nodes: Vec<TokenStream> = Vec::new();
+    let mut pushes: Vec<TokenStream> = Vec::new();
+    let factory = create_factory(info);
+    if factory.is_some() {
+        let mut bases = vec![info.clone()];
+        bases.append(&mut info.get_mro());
+        for (index, base) in bases.iter().filter(|x| !is_generic(x)).enumerate() {
+            let node = format_ident!("FACTORY{}", index);
+            let base_real = base.get_real();
+            let base_pointer = get_dyn_pointer(base);
+            nodes.push(quote!{
+                static #node: registry::Node<registry::Factory<#base_pointer>> = registry::Node::new(registry::Factory {
+                    name: #class_name,
+                    create: |args| create(args).map(|x| -> #base_pointer { x }),
+                });
+            });
+            pushes.push(quote!{
+                <dyn #base_real as registry::Registered>::factories().push(&#node);
+            });
+        }
+    }
+
+    let items = [
+        quote!{
+            impl #name {
+                #[doc(hidden)]
+                pub const __ENTRY__: &'static registry::ClassEntry = &registry::ClassEntry {
+                    name: #class_name,
+                    parent: #parent,
+                    meta: #meta,
+                };
+            }
+        },
+        quote!{
+            impl registry::Registered for dyn #real {
+                type Pointer = #pointer;
+                const CLASS_NAME: &'static str = #class_name;
+                fn factories() -> &'static registry::List<registry::Factory<#pointer>> {
+                    static FACTORIES: registry::List<registry::Factory<#pointer>> = registry::List::new();
+                    &FACTORIES
+                }
+            }
+        },
+        quote!{
+            const _: () = {
+                #factory
+                static ENTRY: registry::Node<&'static registry::ClassEntry> = registry::Node::new(#name::__ENTRY__);
+                #(#nodes)*
+                extern "C" fn register() {
+                    registry::classes_list().push(&ENTRY);
+                    #(#pushes)*
+                }
+                #[used]
+                #[cfg_attr(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly", target_os = "illumos", target_os = "haiku"), link_section = ".init_array")]
+                #[cfg_attr(target_vendor = "apple", link_section = "__DATA,__mod_init_func")]
+                #[cfg_attr(windows, link_section = ".CRT$XCU")]
+                static REGISTER: extern "C" fn() = register;
+            };
+        },
+    ];
+    for item in items {
+        info.extra_items.push(syn::parse2(item).unwrap());
+    }
+}
+
+/// create module <code>registry</code>, which is filled by classes with <code>#\[register\]</code> when the program starts.
+pub fn create_registry(alloc: TokenStream) -> TokenStream {
+    quote!{
+        pub mod registry {
+            #[allow(unused_imports)]
+            use super::{ClassMeta, Value, ReflectError};
+            use ::core::sync::atomic::{AtomicPtr, Ordering};
+
+            /// a registered class.
+            #[derive(Debug)]
+            pub struct ClassEntry {
+                pub name: &'static str,
+                pub parent: ::core::option::Option<&'static ClassEntry>,
+                /// <code>META</code> of the class if its root class has <code>#\[meta\]</code>.
+                pub meta: ::core::option::Option<&'static ClassMeta>,
+            }
+
+            impl ClassEntry {
+                /// this class and its ancestors, from this class to the root class.
+                pub fn mro(&'static self) -> #alloc::vec::Vec<&'static ClassEntry> {
+                    let mut result = #alloc::vec::Vec::new();
+                    let mut current = ::core::option::Option::Some(self);
+                    while let ::core::option::Option::Some(entry) = current {
+                        result.push(entry);
+                        current = entry.parent;
+                    }
+                    result
+                }
+            }
+
+            /// a node of <code>List</code>, which is a static of the registered class.
+            #[doc(hidden)]
+            pub struct Node<T: 'static> {
+                value: T,
+                next: AtomicPtr<Node<T>>,
+            }
+
+            impl<T> Node<T> {
+                pub const fn new(value: T) -> Self {
+                    Node { value, next: AtomicPtr::new(::core::ptr::null_mut()) }
+                }
+            }
+
+            /// a list which nodes are pushed to when the program starts and never removed.
+            #[doc(hidden)]
+            pub struct List<T: 'static> {
+                head: AtomicPtr<Node<T>>,
+            }
+
+            impl<T> List<T> {
+                pub const fn new() -> Self {
+                    List { head: AtomicPtr::new(::core::ptr::null_mut()) }
+                }
+
+                pub fn push(&'static self, node: &'static Node<T>) {
+                    let ptr = node as *const Node<T> as *mut Node<T>;
+                    let mut head = self.head.load(Ordering::Acquire);
+                    loop {
+                        node.next.store(head, Ordering::Relaxed);
+                        match self.head.compare_exchange_weak(head, ptr, Ordering::AcqRel, Ordering::Acquire) {
+                            ::core::result::Result::Ok(_) => return,
+                            ::core::result::Result::Err(x) => head = x,
+                        }
+                    }
+                }
+
+                pub fn iter(&'static self) -> impl ::core::iter::Iterator<Item = &'static T> {
+                    // only nodes which are statics are pushed.
+                    let head = unsafe { self.head.load(Ordering::Acquire).as_ref() };
+                    ::core::iter::successors(head, |x| unsafe { x.next.load(Ordering::Acquire).as_ref() }).map(|x| &x.value)
+                }
+            }
+
+            /// the function creating a class by <code>new</code> as a pointer of one of its bases.
+            #[doc(hidden)]
+            pub struct Factory<P: 'static> {
+                pub name: &'static str,
+                pub create: fn(&[Value]) -> ::core::result::Result<P, ReflectError>,
+            }
+
+            /// <code>dyn \_\_XXX\_\_</code> of a registered class, which can create the class or its subclasses by name.
+            pub trait Registered {
+                type Pointer: 'static;
+                const CLASS_NAME: &'static str;
+                #[doc(hidden)]
+                fn factories() -> &'static List<Factory<Self::Pointer>>;
+            }
+
+            #[doc(hidden)]
+            pub fn classes_list() -> &'static List<&'static ClassEntry> {
+                static CLASSES: List<&'static ClassEntry> = List::new();
+                &CLASSES
+            }
+
+            /// all registered classes, sorted by name.
+            pub fn classes() -> #alloc::vec::Vec<&'static ClassEntry> {
+                let mut result: #alloc::vec::Vec<&'static ClassEntry> = classes_list().iter().copied().collect();
+                result.sort_by_key(|x| x.name);
+                result
+            }
+
+            /// the registered class named <code>name</code>.
+            pub fn get(name: &str) -> ::core::option::Option<&'static ClassEntry> {
+                classes_list().iter().copied().find(|x| x.name == name)
+            }
+
+            /// registered classes which inherit the class named <code>name</code> directly or indirectly, sorted by name.
+            pub fn subclasses_of(name: &str) -> #alloc::vec::Vec<&'static ClassEntry> {
+                classes().into_iter().filter(|x| x.mro().iter().skip(1).any(|x| x.name == name)).collect()
+            }
+
+            /// create the class named <code>name</code> by its <code>new</code>, which should be <code>Base</code> or its subclass,
+            /// like <code>registry::create::<dyn \_\_Shape\_\_>("Square", &[Value::F32(1.0)])</code>.
+            ///
+            /// a registered class which is not <code>Base</code> or its subclass returns <code>ReflectError::NotASubclass</code>,
+            /// and other classes return <code>ReflectError::UnknownMember</code>.
+            pub fn create<Base: ?Sized + Registered>(name: &str, args: &[Value]) -> ::core::result::Result<Base::Pointer, ReflectError> {
+                if let ::core::option::Option::Some(factory) = Base::factories().iter().find(|x| x.name == name) {
+                    return (factory.create)(args);
+                }
+                match get(name) {
+                    ::core::option::Option::Some(entry) if !entry.mro().iter().any(|x| x.name == Base::CLASS_NAME) => {
+                        ::core::result::Result::Err(ReflectError::NotASubclass { name: #alloc::string::String::from(name), base: Base::CLASS_NAME })
+                    },
+                    _ => ::core::result::Result::Err(ReflectError::UnknownMember(#alloc::string::String::from(name))),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! a `no_std` crate using classes, built by `tests/test_no_std.rs`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use core::pin::Pin;
+
+use alloc::boxed::Box;
+use rust_oop::{class, def_class_meta, def_registry};
+
+def_class_meta!(no_std);
+
+class!{
+    #[no_std]
+    #[meta]
+    #[register]
+    struct Device {
+        #[reflect]
+        pin: u32
+    }
+    impl Device {
+        fn get_pin(&self) -> u32 {
+            this.pin
+        }
+        #[validate]
+        fn check_pin(&self) -> Result<(), String> {
+            if this.pin > 40 {
+                return Err(String::from("pin should not be greater than 40"));
+            }
+            Ok(())
+        }
+    }
+}
+
+class!{
+    extends Device;
+    #[builder]
+    pub struct Led {
+        #[reflect]
+        name: String,
+        #[default = 100]
+        brightness: u32
+    }
+    impl Led {
+        #[reflect]
+        fn get_brightness(&self) -> u32 {
+            this.brightness
+        }
+        #[validate]
+        fn check_brightness(&self) -> Result<(), String> {
+            if this.brightness > 255 {
+                return Err(String::from("brightness should not be greater than 255"));
+            }
+            Ok(())
+        }
+    }
+}
+
+def_registry!(no_std);
+
+pub fn build_led(pin: u32, name: String, brightness: u32) -> Result<Pin<Box<Led>>, LedBuildError> {
+    Led::builder().pin(pin).name(name).brightness(brightness).build()
+}
+
+pub fn create_led(pin: u32, name: String) -> Result<Pin<Box<dyn __Device__>>, ReflectError> {
+    registry::create::<dyn __Device__>("Led", &[Value::U32(pin), Value::String(name)])
+}
+
+pub fn describe(device: &dyn __Device__) -> (&'static str, Result<Value, ReflectError>) {
+    (device.class_meta().name, device.get_field("pin"))
+}
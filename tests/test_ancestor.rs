@@ -0,0 +1,66 @@
+use rust_oop::{class, def_as_mut};
+use std::pin::Pin;
+
+def_as_mut!();
+
+#[test]
+fn main_test() {
+    let mut square = Square::new(2.0, 2.0);
+    assert_eq!(&square.name(), "Square");
+    assert_eq!(&square.base_name(), "Shape");
+    assert_eq!(&square.parent_name(), "Rectangle");
+
+    as_mut!(square).reset_scale();
+    assert_eq!(square.get_scale(), 1.0);
+}
+
+class!{
+    struct Shape {
+        scale: f32
+    }
+    impl Shape {
+        fn name(&self) -> String {
+            String::from("Shape")
+        }
+        fn get_scale(&self) -> f32 {
+            this.scale
+        }
+        fn set_scale(&mut self, scale: f32) {
+            this.scale = scale;
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Rectangle {
+        width: f32
+    }
+    impl Rectangle {
+        fn name(&self) -> String {
+            String::from("Rectangle")
+        }
+        fn set_scale(&mut self, scale: f32) {
+            _super_mut.set_scale(scale * this.width);
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct Square { }
+    impl Square {
+        fn name(&self) -> String {
+            String::from("Square")
+        }
+        fn base_name(&self) -> String {
+            _super::<Shape>.name()
+        }
+        fn parent_name(&self) -> String {
+            _super::<Rectangle>.name()
+        }
+        fn reset_scale(&mut self) {
+            _super_mut::<Shape>.set_scale(1.0);
+        }
+    }
+}
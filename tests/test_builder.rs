@@ -0,0 +1,110 @@
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let car = Car::builder()
+        .name(String::from("car1"))
+        ._type(String::from("Car"))
+        .build()
+        .unwrap();
+    assert_eq!(&car.get_type(), "Car");
+    assert_eq!(&car.get_name(), "car1");
+    assert_eq!(car.get_wheels(), 4);
+    assert_eq!(&car.describe(), "Car car1 with 4 wheels");
+
+    let car = Car::builder()
+        ._type(String::from("Car"))
+        .name(String::from("car2"))
+        .wheels(3)
+        .build()
+        .unwrap();
+    assert_eq!(car.get_wheels(), 3);
+    assert_eq!(&car.describe(), "Car car2 with 3 wheels");
+
+    let missing = Car::builder().name(String::from("car3")).build();
+    assert_eq!(missing.err(), Some("missing field `_type` for Car"));
+}
+
+#[test]
+fn validate_test() {
+    let account = Account::builder()
+        .owner(String::from("alice"))
+        .balance(5)
+        .build()
+        .unwrap();
+    assert_eq!(&account.get_owner(), "alice");
+    assert_eq!(account.get_balance(), 5);
+    assert_eq!(account.get_limit(), 100);
+
+    let err = Account::builder().owner(String::from("bob")).balance(-5).build().err();
+    assert!(matches!(err, Some(AccountBuildError::Invalid(x)) if x == "balance should not be negative"));
+    let err = Account::builder().owner(String::from("bob")).limit(-1).build().err();
+    assert!(matches!(err, Some(AccountBuildError::Invalid(x)) if x == "limit should not be negative"));
+    let err = Account::builder().balance(5).build().err();
+    assert!(matches!(err, Some(AccountBuildError::Missing("missing field `owner` for Account"))));
+}
+
+class!{
+    struct Vehicle {
+        _type: String,
+        #[default = 4]
+        wheels: u32
+    }
+    impl Vehicle {
+        fn get_name(&self) -> String { String::from("unnamed") }
+        fn get_type(&self) -> String { this._type.clone() }
+        fn get_wheels(&self) -> u32 { this.wheels }
+        fn describe(&self) -> String {
+            format!("{} {} with {} wheels", self.get_type(), self.get_name(), self.get_wheels())
+        }
+    }
+}
+
+class!{
+    extends Vehicle;
+    #[builder]
+    pub struct Car {
+        name: String
+    }
+    impl Car {
+        fn get_name(&self) -> String { this.name.clone() }
+    }
+}
+
+class!{
+    struct Wallet {
+        #[default = 100]
+        limit: i64
+    }
+    impl Wallet {
+        fn get_limit(&self) -> i64 { this.limit }
+        #[validate]
+        fn check_limit(&self) -> Result<(), String> {
+            if this.limit < 0 {
+                return Err(String::from("limit should not be negative"));
+            }
+            Ok(())
+        }
+    }
+}
+
+class!{
+    extends Wallet;
+    #[builder]
+    pub struct Account {
+        owner: String,
+        #[default = 0]
+        balance: i64
+    }
+    impl Account {
+        fn get_owner(&self) -> String { this.owner.clone() }
+        fn get_balance(&self) -> i64 { this.balance }
+        #[validate]
+        fn check_balance(&self) -> Result<(), String> {
+            if this.balance < 0 {
+                return Err(String::from("balance should not be negative"));
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::{cell::RefCell, pin::Pin, rc::Rc};
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let mut circle = Circle::new(String::from("red"), 1.0);
+    let copy = circle.clone_object();
+    unsafe { circle.as_mut().get_unchecked_mut().radius = 2.0; }
+    assert_eq!(&circle.describe(), "red circle with size 12");
+    assert_eq!(&copy.describe(), "red circle with size 3");
+
+    let shapes: Vec<Pin<Box<dyn __Shape__>>> = vec![copy, Square::new(String::from("blue"), 2.0)];
+    let copies: Vec<Pin<Box<dyn __Shape__>>> = shapes.iter().map(|x| x.clone_dyn()).collect();
+    drop(shapes);
+    assert_eq!(&copies[0].describe(), "red circle with size 3");
+    assert_eq!(&copies[1].describe(), "blue square with size 4");
+
+    let rounded = RoundedSquare::new(String::from("green"), 1.0, 0.5);
+    let copy = rounded.clone_dyn();
+    drop(rounded);
+    assert_eq!(&copy.describe(), "green rounded square with size 1");
+
+    let node = Leaf::new(1);
+    node.push(2);
+    let copy = node.clone_dyn();
+    node.push(3);
+    assert_eq!(node.sum(), 6);
+    assert_eq!(copy.sum(), 3);
+    assert_eq!(Rc::strong_count(&copy), 1);
+}
+
+class!{
+    #[derive(Clone)]
+    struct Shape {
+        color: String
+    }
+    impl Shape {
+        fn name(&self) -> String {
+            String::from("shape")
+        }
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+        fn describe(&self) -> String {
+            format!("{} {} with size {}", this.color, self.name(), self.cal_size())
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    #[derive(Clone)]
+    struct Circle {
+        radius: f32
+    }
+    impl Circle {
+        fn name(&self) -> String {
+            String::from("circle")
+        }
+        fn cal_size(&self) -> f32 {
+            3.0 * this.radius * this.radius
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    #[derive(Clone)]
+    struct Square {
+        side: f32
+    }
+    impl Square {
+        fn name(&self) -> String {
+            String::from("square")
+        }
+        fn cal_size(&self) -> f32 {
+            this.side * this.side
+        }
+    }
+}
+
+class!{
+    extends Square;
+    #[inline_parent]
+    #[derive(Clone)]
+    struct RoundedSquare {
+        radius: f32
+    }
+    impl RoundedSquare {
+        fn name(&self) -> String {
+            String::from("rounded square")
+        }
+    }
+}
+
+class!{
+    #[rc]
+    #[derive(Clone)]
+    struct Node {
+        #[default]
+        values: RefCell<Vec<u32>>
+    }
+    impl Node {
+        fn push(&self, value: u32) {
+            this.values.borrow_mut().push(value);
+        }
+        fn sum(&self) -> u32 {
+            this.values.borrow().iter().sum()
+        }
+    }
+}
+
+class!{
+    extends Node;
+    #[derive(Clone)]
+    struct Leaf {
+        value: u32
+    }
+    impl Leaf {
+        fn sum(&self) -> u32 {
+            this.value + _super.sum()
+        }
+    }
+}
@@ -0,0 +1,5 @@
+#[test]
+fn main_test() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,111 @@
+use rust_oop::{class, def_as_mut};
+use std::pin::Pin;
+
+def_as_mut!();
+
+#[test]
+fn main_test() {
+    let mut square = Square::new(-2.0);
+    assert_eq!(square.get_len(), 2.0);
+    assert_eq!(square.cal_size(), 4.0);
+    assert_eq!(&square.describe(), "Square 2x2");
+    as_mut!(square).set_width(3.0);
+    assert_eq!(&square.describe(), "Square 3x2");
+
+    let cube = Cube::new(2.0, 5.0);
+    assert_eq!(cube.cal_size(), 20.0);
+    assert_eq!(&cube.name(), "Cube");
+    assert_eq!(&cube.describe(), "Cube 2x2");
+
+    let unit = UnitSquare::new();
+    assert_eq!(unit.cal_size(), 1.0);
+    assert_eq!(&unit.describe(), "Rectangle 1x1");
+}
+
+#[test]
+#[should_panic(expected = "`__real__` is null")]
+fn unpinned_test() {
+    // breaks the safety contract on purpose, `self` should panic instead of dereferencing null.
+    let rectangle = unsafe { Rectangle::__new_unpinned__(1.0, 2.0) };
+    rectangle.describe();
+}
+
+class!{
+    struct Rectangle {
+        width: f32,
+        height: f32,
+        area: f32
+    }
+    impl Rectangle {
+        #[constructor]
+        pub fn new(width: f32, height: f32) {
+            let area = width * height;
+            Self { width, height, area }
+        }
+        #[constructor]
+        pub fn unit() {
+            Self { width: 1.0, height: 1.0, area: 1.0 }
+        }
+        fn name(&self) -> String {
+            String::from("Rectangle")
+        }
+        fn describe(&self) -> String {
+            format!("{} {}x{}", self.name(), this.width, this.height)
+        }
+        fn cal_size(&self) -> f32 {
+            this.area
+        }
+        fn set_width(&mut self, width: f32) {
+            this.width = width;
+            this.area = this.width * this.height;
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct Square {
+        len: f32
+    }
+    impl Square {
+        #[constructor]
+        pub fn new(len: f32) {
+            let side = len.abs();
+            super_new!(side, side);
+            Self { len: side }
+        }
+        fn name(&self) -> String {
+            String::from("Square")
+        }
+        fn get_len(&self) -> f32 {
+            this.len
+        }
+    }
+}
+
+class!{
+    extends Square;
+    struct Cube {
+        depth: f32
+    }
+    impl Cube {
+        fn name(&self) -> String {
+            String::from("Cube")
+        }
+        fn cal_size(&self) -> f32 {
+            _super.cal_size() * this.depth
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct UnitSquare { }
+    impl UnitSquare {
+        #[constructor]
+        pub fn new() {
+            super_new!(unit;);
+            Self { }
+        }
+    }
+}
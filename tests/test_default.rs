@@ -0,0 +1,62 @@
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let car = Car::new(String::from("car1"));
+    assert_eq!(&car.get_type(), "Car");
+    assert_eq!(car.get_wheels(), 4);
+    assert_eq!(car.get_passengers(), 0);
+    assert_eq!(&car.get_name(), "car1");
+    assert!(car.get_tags().is_empty());
+
+    let truck = Truck::new(String::from("truck1"), 2.5);
+    assert_eq!(&truck.get_type(), "Truck");
+    assert_eq!(truck.get_wheels(), 6);
+    assert_eq!(truck.get_load(), 2.5);
+}
+
+class!{
+    struct Vehicle {
+        #[default = String::from("Car")]
+        _type: String,
+        #[default = 4]
+        wheels: u32,
+        #[default]
+        passengers: u32
+    }
+    impl Vehicle {
+        fn get_type(&self) -> String { this._type.clone() }
+        fn get_wheels(&self) -> u32 { this.wheels }
+        fn get_passengers(&self) -> u32 { this.passengers }
+    }
+}
+
+class!{
+    extends Vehicle;
+    struct Car {
+        name: String,
+        #[default]
+        tags: Vec<String>
+    }
+    impl Car {
+        fn get_name(&self) -> String { this.name.clone() }
+        fn get_tags(&self) -> Vec<String> { this.tags.clone() }
+    }
+}
+
+class!{
+    extends Car;
+    struct Truck {
+        load: f32
+    }
+    impl Truck {
+        #[constructor]
+        fn new(name: String, load: f32) {
+            super_new!(name);
+            Self { load }
+        }
+        fn get_type(&self) -> String { String::from("Truck") }
+        fn get_wheels(&self) -> u32 { _super.get_wheels() + 2 }
+        fn get_load(&self) -> f32 { this.load }
+    }
+}
@@ -0,0 +1,112 @@
+use std::rc::Rc;
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let mut square = Square::new(2.0);
+    assert_eq!(square.width, 2.0);
+    assert_eq!(&square.color, "red");
+    assert_eq!(square.area(), 4.0);
+
+    unsafe { square.as_mut().parent_pin().get_unchecked_mut() }.resize(3.0, 4.0);
+    assert_eq!(square.height, 4.0);
+    assert_eq!(square.cal_size(), 12.0);
+    square.as_mut().parent_pin().parent_pin().color_pin().get_mut().push_str("dish");
+    assert_eq!(&square.color, "reddish");
+
+    let mut cube = Cube::new(2.0, 3.0);
+    assert_eq!(cube.depth, 3.0);
+    assert_eq!(cube.size, 2.0);
+    assert_eq!(cube.width, 2.0);
+    assert_eq!(cube.area(), 4.0);
+    assert_eq!(*cube.as_mut().parent_pin().parent_pin().height_pin(), 2.0);
+    *cube.as_mut().parent_pin().parent_pin().width_mut() = 5.0;
+    assert_eq!(cube.area(), 10.0);
+
+    let leaf = Leaf::new(1, 2);
+    assert_eq!(leaf.value, 1);
+    assert_eq!(leaf.weight, 2);
+    let node: Rc<dyn __Node__> = leaf;
+    assert_eq!(node.get_value(), 1);
+}
+
+class!{
+    struct Shape {
+        #[pin]
+        color: String
+    }
+    impl Shape {
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    #[deref]
+    struct Rectangle {
+        width: f32,
+        #[pin]
+        height: f32
+    }
+    impl Rectangle {
+        #[keep]
+        pub fn area(&self) -> f32 {
+            this.width * this.height
+        }
+        #[keep]
+        pub fn resize(&mut self, width: f32, height: f32) {
+            this.width = width;
+            this.height = height;
+        }
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct Square {
+        size: f32
+    }
+    impl Square {
+        #[constructor]
+        fn new(size: f32) {
+            super_new!(String::from("red"), size, size);
+            Self { size }
+        }
+    }
+}
+
+class!{
+    extends Square;
+    #[inline_parent]
+    struct Cube {
+        depth: f32
+    }
+    impl Cube { }
+}
+
+class!{
+    #[rc]
+    #[deref]
+    struct Node {
+        value: i32
+    }
+    impl Node {
+        fn get_value(&self) -> i32 {
+            this.value
+        }
+    }
+}
+
+class!{
+    extends Node;
+    struct Leaf {
+        weight: i32
+    }
+    impl Leaf { }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let cat = Cat::new(String::from("Tom"), 3, true);
+    assert_eq!(format!("{:?}", cat), "Cat { name: \"Tom\", age: 3, indoor: true }");
+    assert_eq!(cat.describe(), "Tom is an indoor cat");
+
+    assert_eq!(*cat, *Cat::new(String::from("Tom"), 3, true));
+    assert_ne!(*cat, *Cat::new(String::from("Tom"), 4, true));
+    assert_ne!(*cat, *Cat::new(String::from("Tom"), 3, false));
+
+    let mut cats = HashSet::new();
+    cats.insert(cat);
+    cats.insert(Cat::new(String::from("Tom"), 3, true));
+    cats.insert(Cat::new(String::from("Jerry"), 3, true));
+    assert_eq!(cats.len(), 2);
+
+    let point = Point::new(1, 2);
+    assert_eq!(format!("{:?}", point), "Point { x: 1, y: 2 }");
+    assert_eq!(*point, *Point::new(1, 2));
+}
+
+class!{
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct Animal {
+        name: String,
+        age: u32
+    }
+    impl Animal {
+        fn kind(&self) -> String {
+            String::from("animal")
+        }
+        fn describe(&self) -> String {
+            format!("{} is {}", this.name, self.kind())
+        }
+    }
+}
+
+class!{
+    extends Animal;
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct Cat {
+        indoor: bool
+    }
+    impl Cat {
+        fn kind(&self) -> String {
+            format!("an {} cat", if this.indoor { "indoor" } else { "outdoor" })
+        }
+    }
+}
+
+class!{
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32
+    }
+    impl Point { }
+}
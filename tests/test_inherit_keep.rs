@@ -0,0 +1,157 @@
+use std::pin::Pin;
+
+use rust_oop::{class, def_as_mut};
+
+def_as_mut!();
+
+#[test]
+fn main_test() {
+    let mut square = Square::new(2.0);
+    assert_eq!(square.get_width(), 2.0);
+    as_mut!(square).set_width(3.0);
+    assert_eq!(square.get_width(), 3.0);
+    assert_eq!(square.cal_size(), 6.0);
+    assert_eq!(&square.describe(), "Square 3x2");
+    assert_eq!(Square::default_width(), 1.0);
+    assert_eq!(Square::sides(), 4);
+
+    let mut cube = Cube::new(2.0, 5.0);
+    as_mut!(cube).set_width(4.0);
+    assert_eq!(cube.get_width(), 4.0);
+    assert_eq!(cube.cal_size(), 40.0);
+    assert_eq!(&cube.describe(), "Square 4x2");
+    assert_eq!(Cube::default_width(), 1.0);
+    assert_eq!(Cube::sides(), 6);
+
+    assert_eq!(&Rectangle::unit().describe(), "Rectangle 1x1");
+    assert_eq!(&Square::unit().describe(), "Square 1x1");
+
+    let mut label = Label::new(String::from("ok"), 2);
+    assert_eq!(&label.get_text(), "ok");
+    as_mut!(label).set_text(String::from("done"));
+    assert_eq!(&label.get_text(), "done");
+    assert_eq!(label.size, 2);
+
+    let node = Leaf::new(1, 2);
+    assert_eq!(node.get_value(), 1);
+    assert_eq!(node.weight, 2);
+}
+
+class!{
+    struct Rectangle {
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        #[keep]
+        pub fn default_width() -> f32 {
+            1.0
+        }
+        #[keep]
+        pub fn unit() -> Pin<Box<Self>> {
+            Self::new(1.0, 1.0)
+        }
+        #[keep]
+        #[class_method]
+        fn sides() -> u32 {
+            4
+        }
+        #[keep]
+        pub fn get_width(&self) -> f32 {
+            this.width
+        }
+        #[keep]
+        pub fn set_width(&mut self, width: f32) {
+            this.width = width;
+        }
+        #[keep]
+        pub fn describe(&self) -> String {
+            format!("Rectangle {}x{}", this.width, this.height)
+        }
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct Square { }
+    impl Square {
+        #[constructor]
+        fn new(size: f32) {
+            super_new!(size, size);
+            Self { }
+        }
+        #[keep]
+        pub fn unit() -> Pin<Box<Self>> {
+            Self::new(1.0)
+        }
+        #[keep]
+        pub fn describe(&self) -> String {
+            format!("Square {}x{}", this.get_width(), _super.cal_size() / this.get_width())
+        }
+    }
+}
+
+class!{
+    extends Square;
+    struct Cube {
+        depth: f32
+    }
+    impl Cube {
+        #[class_method]
+        fn sides() -> u32 {
+            6
+        }
+        fn cal_size(&self) -> f32 {
+            _super.cal_size() * this.depth
+        }
+    }
+}
+
+class!{
+    struct Text {
+        text: String
+    }
+    impl Text {
+        #[keep]
+        fn get_text(&self) -> String {
+            this.text.clone()
+        }
+        #[keep]
+        fn set_text(&mut self, text: String) {
+            this.text = text;
+        }
+    }
+}
+
+class!{
+    extends Text;
+    #[inline_parent]
+    struct Label {
+        size: u32
+    }
+    impl Label { }
+}
+
+class!{
+    #[rc]
+    struct Node {
+        value: i32
+    }
+    impl Node {
+        #[keep]
+        fn get_value(&self) -> i32 {
+            this.value
+        }
+    }
+}
+
+class!{
+    extends Node;
+    struct Leaf {
+        weight: i32
+    }
+    impl Leaf { }
+}
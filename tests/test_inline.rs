@@ -0,0 +1,131 @@
+use std::{mem::size_of, pin::Pin};
+
+use rust_oop::{class, def_as_mut};
+
+def_as_mut!();
+
+#[test]
+fn main_test() {
+    assert!(size_of::<Square>() >= size_of::<Rectangle>());
+    assert!(size_of::<Rectangle>() >= size_of::<Shape>());
+
+    let mut shape = Square::new(2.0);
+    let prototype: *const Rectangle = &shape.__prototype__;
+    assert!(!std::ptr::addr_eq(prototype, &*shape as *const Square));
+    assert_eq!(&shape.name(), "Square");
+    assert_eq!(&shape.describe(), "Square with size 4");
+    assert_eq!(&shape.base_name(), "Shape");
+
+    as_mut!(shape).set_length(3.0);
+    assert_eq!(shape.cal_size(), 9.0);
+    as_mut!(shape).scale(2.0);
+    assert_eq!(shape.cal_size(), 36.0);
+    assert_eq!(shape.get_scaled(), 1);
+
+    let mut cube = Cube::new(2.0, 3.0);
+    assert_eq!(&cube.describe(), "Cube with size 12");
+    as_mut!(cube).set_length(1.0);
+    assert_eq!(cube.cal_size(), 3.0);
+
+    let err = Rectangle::try_new(-1.0, 2.0).err();
+    assert_eq!(err, Some(String::from("Rectangle should not have negative size")));
+}
+
+class!{
+    struct Shape {
+        #[default]
+        scaled: u32
+    }
+    impl Shape {
+        #[validate]
+        fn check(&self) -> Result<(), String> {
+            if self.cal_size() < 0.0 {
+                return Err(format!("{} should not have negative size", self.name()));
+            }
+            Ok(())
+        }
+        fn name(&self) -> String {
+            String::from("Shape")
+        }
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+        fn describe(&self) -> String {
+            format!("{} with size {}", self.name(), self.cal_size())
+        }
+        #[allow(unused_variables)]
+        fn scale(&mut self, times: f32) {
+            this.scaled += 1;
+        }
+        fn get_scaled(&self) -> u32 {
+            this.scaled
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    #[inline_parent]
+    struct Rectangle {
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        fn name(&self) -> String {
+            String::from("Rectangle")
+        }
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+        fn scale(&mut self, times: f32) {
+            this.width *= times;
+            this.height *= times;
+            _super_mut.scale(times);
+        }
+        fn set_width(&mut self, width: f32) {
+            this.width = width;
+        }
+        fn set_height(&mut self, height: f32) {
+            this.height = height;
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    #[inline_parent]
+    struct Square { }
+    impl Square {
+        #[constructor]
+        fn new(len: f32) {
+            super_new!(len, len);
+            Self { }
+        }
+        fn name(&self) -> String {
+            String::from("Square")
+        }
+        fn base_name(&self) -> String {
+            _super::<Shape>.name()
+        }
+        fn set_length(&mut self, len: f32) {
+            _super_mut.set_width(len);
+            _super_mut.set_height(len);
+        }
+    }
+}
+
+class!{
+    extends Square;
+    #[inline_parent]
+    struct Cube {
+        depth: f32
+    }
+    impl Cube {
+        fn name(&self) -> String {
+            String::from("Cube")
+        }
+        fn cal_size(&self) -> f32 {
+            _super.cal_size() * this.depth
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::pin::Pin;
+
+use rust_oop::{class, def_as_mut, def_class_meta};
+
+def_as_mut!();
+def_class_meta!();
+
+#[test]
+fn main_test() {
+    let mut rectangle = Rectangle::new(String::from("red"), 2.0, 3.0);
+    let shape: &mut dyn __Shape__ = as_mut!(rectangle);
+    assert_eq!(shape.invoke("cal_size", &[]), Ok(Value::F32(6.0)));
+    assert_eq!(shape.invoke("set_width", &[Value::F32(3.0)]), Ok(Value::Unit));
+    assert_eq!(shape.invoke("cal_size", &[]), Ok(Value::F32(9.0)));
+    assert_eq!(shape.invoke("describe", &[Value::String(String::from("a"))]), Ok(Value::String(String::from("a red shape with size 9"))));
+
+    assert_eq!(shape.get_field("width"), Ok(Value::F32(3.0)));
+    assert_eq!(shape.get_field("color"), Ok(Value::String(String::from("red"))));
+    assert_eq!(shape.set_field("color", Value::String(String::from("blue"))), Ok(()));
+    assert_eq!(shape.get_field("color"), Ok(Value::String(String::from("blue"))));
+
+    assert_eq!(shape.invoke("draw", &[]), Err(ReflectError::UnknownMember(String::from("draw"))));
+    assert_eq!(shape.get_field("height"), Err(ReflectError::UnknownMember(String::from("height"))));
+    assert_eq!(shape.invoke("set_width", &[]), Err(ReflectError::WrongArity { name: "set_width", expected: 1, found: 0 }));
+    let err = shape.invoke("set_width", &[Value::I32(3)]).unwrap_err();
+    assert_eq!(err, ReflectError::TypeMismatch { name: "set_width", index: 0, expected: "f32" });
+    assert_eq!(&err.to_string(), "argument 0 of `set_width` should be f32");
+    assert_eq!(shape.set_field("width", Value::Bool(true)), Err(ReflectError::TypeMismatch { name: "width", index: 0, expected: "f32" }));
+    assert_eq!(rectangle.height, 3.0);
+}
+
+class!{
+    #[meta]
+    struct Shape {
+        #[reflect]
+        color: String
+    }
+    impl Shape {
+        #[reflect]
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+        #[reflect]
+        fn describe(&self, prefix: String) -> String {
+            format!("{} {} shape with size {}", prefix, this.color, self.cal_size())
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Rectangle {
+        #[reflect]
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+        #[reflect]
+        fn set_width(&mut self, width: f32) {
+            this.width = width;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use rust_oop::{class, def_class_meta};
+
+def_class_meta!();
+
+#[test]
+fn main_test() {
+    assert_eq!(Shape::META.name, "Shape");
+    assert!(Shape::META.parent.is_none());
+    assert_eq!(Rectangle::META.parent.unwrap().name, "Shape");
+
+    let fields: Vec<(&str, &str, &str)> = Rectangle::META.all_fields().iter().map(|x| (x.name, x.type_name, x.class)).collect();
+    assert_eq!(fields, vec![
+        ("color", "String", "Shape"),
+        ("tags", "Vec<&'static str>", "Shape"),
+        ("width", "f32", "Rectangle"),
+        ("height", "f32", "Rectangle"),
+    ]);
+
+    let rectangle = Rectangle::new(String::from("red"), Vec::new(), 2.0, 3.0);
+    let shape: &dyn __Shape__ = &*rectangle;
+    let meta = shape.class_meta();
+    assert_eq!(meta.name, "Rectangle");
+    let methods: Vec<(&str, &str, &str)> = meta.all_methods().iter().map(|x| (x.name, x.signature, x.class)).collect();
+    assert_eq!(methods, vec![
+        ("cal_size", "fn cal_size(&self) -> f32", "Rectangle"),
+        ("describe", "fn describe(&self) -> String", "Shape"),
+        ("set_width", "fn set_width(&mut self, width: f32)", "Rectangle"),
+    ]);
+    assert_eq!(meta.field("color").unwrap().class, "Shape");
+    assert_eq!(meta.method("describe").unwrap().class, "Shape");
+    assert!(meta.method("draw").is_none());
+    assert_eq!(&shape.describe(), "red shape with size 6 []");
+    assert_eq!(meta.mro().iter().map(|x| x.name).collect::<Vec<_>>(), vec!["Rectangle", "Shape"]);
+}
+
+class!{
+    #[meta]
+    struct Shape {
+        color: String,
+        tags: Vec<&'static str>
+    }
+    impl Shape {
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+        fn describe(&self) -> String {
+            format!("{} shape with size {} {:?}", this.color, self.cal_size(), this.tags)
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Rectangle {
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+        fn set_width(&mut self, width: f32) {
+            this.width = width;
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::pin::Pin;
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    assert_eq!(Shape::class().class_name(), "Shape");
+    assert_eq!(Square::class().display_name(), "square");
+    assert_eq!(Square::class().sides(), 4);
+    assert_eq!(Rectangle::class().sides(), 4);
+
+    let classes: [&dyn __ShapeClass__; 3] = [Shape::class(), Rectangle::class(), Square::class()];
+    let names: Vec<String> = classes.iter().map(|x| x.display_name()).collect();
+    assert_eq!(names, ["shape", "rectangle", "square"]);
+    let sizes: Vec<f32> = classes.iter().map(|x| x.default_instance().cal_size()).collect();
+    assert_eq!(sizes, [0.0, 2.0, 1.0]);
+
+    let shape: Pin<Box<dyn __Shape__>> = Square::new(3.0);
+    assert_eq!(shape.class().class_name(), "Square");
+    assert_eq!(shape.class().display_name(), "square");
+    assert_eq!(shape.class().default_instance().class_name(), "Square");
+
+    let rectangle: &dyn __RectangleClass__ = Square::class();
+    assert_eq!(rectangle.with_size(2.0).cal_size(), 4.0);
+    assert_eq!(Rectangle::class().with_size(2.0).cal_size(), 8.0);
+    assert_eq!(Square::with_size(2.0).cal_size(), 4.0);
+}
+
+#[test]
+fn without_class_method_test() {
+    let circle = Circle::new(1.0);
+    assert_eq!(CircleClass { radius: 2.0 }.radius, 2.0);
+    assert_eq!(circle.class_name(), "Circle");
+
+    let ring = Ring::new(1.0);
+    assert_eq!(Ring::class().hole(), 0.5);
+    assert_eq!(ring.class().class_name(), "Ring");
+    assert_eq!(ring.get_radius(), 1.0);
+}
+
+class!{
+    struct Shape { }
+    impl Shape {
+        #[class_method]
+        fn display_name() -> String {
+            String::from("shape")
+        }
+        #[class_method]
+        fn default_instance() -> Pin<Box<dyn __Shape__>> {
+            Shape::new()
+        }
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Rectangle {
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        #[class_method]
+        fn display_name() -> String {
+            String::from("rectangle")
+        }
+        #[class_method]
+        fn default_instance() -> Pin<Box<dyn __Shape__>> {
+            Rectangle::new(1.0, 2.0)
+        }
+        #[class_method]
+        fn sides() -> u32 {
+            4
+        }
+        #[class_method]
+        fn with_size(size: f32) -> Pin<Box<dyn __Rectangle__>> {
+            Rectangle::new(size, size * 2.0)
+        }
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct Square { }
+    impl Square {
+        #[constructor]
+        fn new(size: f32) {
+            super_new!(size, size);
+            Self { }
+        }
+        #[class_method]
+        fn display_name() -> String {
+            String::from("square")
+        }
+        #[class_method]
+        fn default_instance() -> Pin<Box<dyn __Shape__>> {
+            Square::new(1.0)
+        }
+        #[class_method]
+        fn with_size(size: f32) -> Pin<Box<dyn __Rectangle__>> {
+            Square::new(size)
+        }
+    }
+}
+
+/// a user type named like a metaclass.
+struct CircleClass {
+    radius: f32
+}
+
+class!{
+    struct Circle {
+        radius: f32
+    }
+    impl Circle {
+        fn get_radius(&self) -> f32 {
+            this.radius
+        }
+    }
+}
+
+class!{
+    extends Circle;
+    struct Ring { }
+    impl Ring {
+        #[class_method]
+        fn hole() -> f32 {
+            0.5
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let car = Car::new(String::from("vehicle1"), String::from("land1"), String::from("car1"));
+    assert_eq!(&car.get_name(), "car1");
+    assert_eq!(&car.get_land_name(), "land1");
+    assert_eq!(&car.get_vehicle_name(), "vehicle1");
+
+    let car = Car::builder()
+        .vehicle_name(String::from("vehicle2"))
+        .land_vehicle_name(String::from("land2"))
+        .name(String::from("car2"))
+        .build()
+        .unwrap();
+    assert_eq!(&car.get_name(), "car2");
+    assert_eq!(&car.get_land_name(), "land2");
+    assert_eq!(&car.get_vehicle_name(), "vehicle2");
+}
+
+class!{
+    struct Vehicle {
+        name: String
+    }
+    impl Vehicle {
+        fn get_name(&self) -> String { this.name.clone() }
+        fn get_vehicle_name(&self) -> String { this.name.clone() }
+    }
+}
+
+class!{
+    extends Vehicle;
+    struct LandVehicle {
+        name: String
+    }
+    impl LandVehicle {
+        fn get_name(&self) -> String { this.name.clone() }
+        fn get_land_name(&self) -> String { this.name.clone() }
+    }
+}
+
+class!{
+    extends LandVehicle;
+    #[builder]
+    struct Car {
+        name: String
+    }
+    impl Car {
+        fn get_name(&self) -> String { this.name.clone() }
+    }
+}
@@ -0,0 +1,118 @@
+extern crate alloc;
+
+use alloc::{rc::Rc, string::String};
+
+use rust_oop::{class, def_class_meta};
+
+def_class_meta!(no_std);
+
+#[test]
+fn main_test() {
+    let led = Led::new(3, true);
+    assert_eq!(led.get_pin(), 3);
+    assert_eq!(led.describe(), (3, "led"));
+    assert!(led.is_on());
+
+    let button = Button::new(5);
+    assert_eq!(button.describe(), (5, "button"));
+    assert_eq!(Rc::strong_count(&button), 1);
+}
+
+/// build `tests/no_std`, a `#![no_std]` crate using builder, validators, meta, reflect and registry.
+#[test]
+fn fixture_test() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let output = std::process::Command::new(env!("CARGO"))
+        .args(["build", "--offline", "--manifest-path"])
+        .arg(format!("{}/tests/no_std/Cargo.toml", root))
+        .arg("--target-dir")
+        .arg(format!("{}/target/no_std", root))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn meta_test() {
+    let fields: alloc::vec::Vec<&str> = Sensor::META.all_fields().iter().map(|x| x.name).collect();
+    assert_eq!(fields, ["name", "value"]);
+
+    let sensor = Sensor::new(String::from("sensor"), 2);
+    assert_eq!(sensor.class_meta().name, "Sensor");
+    assert_eq!(sensor.get_field("name"), Ok(Value::String(String::from("sensor"))));
+    assert_eq!(sensor.get_field("unit"), Err(ReflectError::UnknownMember(String::from("unit"))));
+}
+
+class!{
+    #[no_std]
+    struct Device {
+        pin: u8
+    }
+    impl Device {
+        fn get_pin(&self) -> u8 {
+            this.pin
+        }
+        fn kind(&self) -> &'static str {
+            "device"
+        }
+        fn describe(&self) -> (u8, &'static str) {
+            (self.get_pin(), self.kind())
+        }
+    }
+}
+
+class!{
+    extends Device;
+    struct Led {
+        on: bool
+    }
+    impl Led {
+        fn kind(&self) -> &'static str {
+            "led"
+        }
+        fn is_on(&self) -> bool {
+            this.on
+        }
+    }
+}
+
+class!{
+    #[no_std]
+    #[rc]
+    struct Input {
+        pin: u8
+    }
+    impl Input {
+        fn kind(&self) -> &'static str {
+            "input"
+        }
+        fn describe(&self) -> (u8, &'static str) {
+            (this.pin, self.kind())
+        }
+    }
+}
+
+class!{
+    extends Input;
+    struct Button { }
+    impl Button {
+        fn kind(&self) -> &'static str {
+            "button"
+        }
+    }
+}
+
+class!{
+    #[no_std]
+    #[meta]
+    struct Sensor {
+        #[reflect]
+        name: String,
+        value: i32
+    }
+    impl Sensor {
+        fn get_value(&self) -> i32 {
+            this.value
+        }
+    }
+}
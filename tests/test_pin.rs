@@ -0,0 +1,54 @@
+use std::{future::Future, marker::PhantomPinned, pin::Pin, task::{Context, Poll, Waker}};
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let mut task = Task::new(String::from("task1"), Countdown::new(2));
+    assert_eq!(&task.get_name(), "task1");
+
+    let mut cx = Context::from_waker(Waker::noop());
+    assert_eq!(task.as_mut().countdown_pin().poll(&mut cx), Poll::Pending);
+    assert_eq!(task.as_mut().countdown_pin().poll(&mut cx), Poll::Pending);
+    assert_eq!(task.as_mut().countdown_pin().poll(&mut cx), Poll::Ready(String::from("done")));
+
+    task.as_mut().name_mut().push_str("-finished");
+    assert_eq!(&task.get_name(), "task1-finished");
+}
+
+struct Countdown {
+    count: u32,
+    _pinned: PhantomPinned,
+}
+
+impl Countdown {
+    fn new(count: u32) -> Self {
+        Countdown { count, _pinned: PhantomPinned }
+    }
+}
+
+impl Future for Countdown {
+    type Output = String;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<String> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.count == 0 {
+            return Poll::Ready(String::from("done"));
+        }
+        this.count -= 1;
+        Poll::Pending
+    }
+}
+
+class!{
+    struct Task {
+        name: String,
+        #[pin]
+        countdown: Countdown
+    }
+    impl Task {
+        fn get_name(&self) -> String {
+            this.name.clone()
+        }
+    }
+}
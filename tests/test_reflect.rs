@@ -0,0 +1,89 @@
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    assert_eq!(Car::CLASS_NAME, "Car");
+    assert_eq!(Car::PARENT_CLASS_NAME, Some("LandVehicle"));
+    assert_eq!(Car::MRO, &["Car", "LandVehicle", "Vehicle"]);
+    assert_eq!(Vehicle::PARENT_CLASS_NAME, None);
+    assert_eq!(Vehicle::MRO, &["Vehicle"]);
+
+    let car = Car::new(String::from("car1"));
+    assert_eq!(car.class_name(), "Car");
+    assert_eq!(car.describe(), "car1 is a Car");
+
+    let land_vehicle = LandVehicle::new();
+    let vehicle = Vehicle::new();
+    let vehicles: Vec<&dyn __Vehicle__> = vec![&*car, &*land_vehicle, &*vehicle];
+    let names: Vec<&str> = vehicles.iter().map(|x| x.class_name()).collect();
+    assert_eq!(names, vec!["Car", "LandVehicle", "Vehicle"]);
+    assert_eq!(vehicles[0].parent_class_name(), Some("LandVehicle"));
+    assert_eq!(vehicles[1].mro(), &["LandVehicle", "Vehicle"]);
+    assert_eq!(vehicles[2].parent_class_name(), None);
+}
+
+#[test]
+fn user_defined_test() {
+    let child = Child::new(1);
+    assert_eq!(child.mro(), vec![String::from("resolved")]);
+    assert_eq!(child.class_name(), "Child");
+    assert_eq!(Child::MRO, &["Child", "Base"]);
+    assert_eq!(Base::CLASS_NAME, "base");
+
+    let base: &dyn __Base__ = &*child;
+    assert_eq!(base.mro(), vec![String::from("resolved")]);
+    assert_eq!(base.class_name(), "Child");
+}
+
+class!{
+    struct Vehicle { }
+    impl Vehicle {
+        fn get_name(&self) -> String {
+            String::from("unnamed")
+        }
+        fn describe(&self) -> String {
+            format!("{} is a {}", self.get_name(), self.class_name())
+        }
+    }
+}
+
+class!{
+    extends Vehicle;
+    struct LandVehicle { }
+    impl LandVehicle { }
+}
+
+class!{
+    extends LandVehicle;
+    struct Car {
+        name: String
+    }
+    impl Car {
+        fn get_name(&self) -> String {
+            this.name.clone()
+        }
+    }
+}
+
+class!{
+    struct Base { }
+    impl Base {
+        const CLASS_NAME: &'static str = "base";
+        /// names of the most derived class and its ancestors.
+        fn mro(&self) -> Vec<String> {
+            vec![String::from("resolved")]
+        }
+    }
+}
+
+class!{
+    extends Base;
+    struct Child {
+        value: i32
+    }
+    impl Child {
+        fn get_value(&self) -> i32 {
+            this.value
+        }
+    }
+}
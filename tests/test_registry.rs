@@ -0,0 +1,158 @@
+use std::rc::Rc;
+
+use rust_oop::{class, def_class_meta, def_registry};
+
+def_class_meta!();
+
+#[test]
+fn main_test() {
+    let names: Vec<&str> = registry::classes().iter().map(|x| x.name).collect();
+    assert_eq!(names, ["Account", "Circle", "Leaf", "Node", "Point", "Rectangle", "Shape", "Square"]);
+    assert_eq!(registry::get("Square").unwrap().parent.unwrap().name, "Rectangle");
+    assert_eq!(registry::get("Square").unwrap().meta.unwrap().name, "Square");
+    assert!(registry::get("Point").unwrap().meta.is_none());
+    assert_eq!(registry::create::<dyn __Point__>("Point", &[Value::I32(1)]).unwrap().get_x(), 1);
+
+    let names: Vec<&str> = registry::subclasses_of("Shape").iter().map(|x| x.name).collect();
+    assert_eq!(names, ["Circle", "Rectangle", "Square"]);
+    let names: Vec<&str> = registry::subclasses_of("Rectangle").iter().map(|x| x.name).collect();
+    assert_eq!(names, ["Square"]);
+    assert!(registry::subclasses_of("Square").is_empty());
+
+    let shape = registry::create::<dyn __Shape__>("Square", &[Value::F32(2.0)]).unwrap();
+    assert_eq!(shape.class_name(), "Square");
+    assert_eq!(shape.cal_size(), 4.0);
+    let shape = registry::create::<dyn __Shape__>("Rectangle", &[Value::F32(2.0), Value::F32(3.0)]).unwrap();
+    assert_eq!(shape.cal_size(), 6.0);
+    let shapes: Vec<_> = registry::subclasses_of("Shape").iter()
+        .filter_map(|x| registry::create::<dyn __Shape__>(x.name, &[Value::F32(1.0)]).ok())
+        .map(|x| x.class_name())
+        .collect();
+    assert_eq!(shapes, ["Circle", "Square"]);
+
+    let rectangle = registry::create::<dyn __Rectangle__>("Square", &[Value::F32(3.0)]).unwrap();
+    assert_eq!(rectangle.cal_size(), 9.0);
+    let err = registry::create::<dyn __Rectangle__>("Circle", &[Value::F32(1.0)]).err().unwrap();
+    assert_eq!(err, ReflectError::NotASubclass { name: String::from("Circle"), base: "Rectangle" });
+    assert_eq!(&err.to_string(), "Circle is not a subclass of Rectangle");
+    assert_eq!(registry::create::<dyn __Shape__>("Shape", &[]).unwrap().cal_size(), 0.0);
+    assert_eq!(registry::create::<dyn __Shape__>("Triangle", &[]).err(), Some(ReflectError::UnknownMember(String::from("Triangle"))));
+    assert_eq!(registry::create::<dyn __Shape__>("Square", &[]).err(), Some(ReflectError::WrongArity { name: "Square", expected: 1, found: 0 }));
+    assert_eq!(registry::create::<dyn __Shape__>("Square", &[Value::I32(1)]).err(), Some(ReflectError::TypeMismatch { name: "Square", index: 0, expected: "f32" }));
+
+    let node: Rc<dyn __Node__> = registry::create::<dyn __Node__>("Leaf", &[Value::I32(1), Value::String(String::from("a"))]).unwrap();
+    assert_eq!(node.describe(), "1 a");
+
+    let account = registry::create::<dyn __Account__>("Account", &[Value::I64(5)]).unwrap();
+    assert_eq!(account.get_balance(), 5);
+    let err = registry::create::<dyn __Account__>("Account", &[Value::I64(-5)]).err().unwrap();
+    assert_eq!(err, ReflectError::Invalid { name: "Account", error: String::from("\"balance should not be negative\"") });
+    assert_eq!(&err.to_string(), "failed to create Account: \"balance should not be negative\"");
+}
+
+class!{
+    #[meta]
+    #[register]
+    struct Shape { }
+    impl Shape {
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Rectangle {
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct Square { }
+    impl Square {
+        #[constructor]
+        fn new(size: f32) {
+            super_new!(size, size);
+            Self { }
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Circle {
+        radius: f32
+    }
+    impl Circle {
+        fn cal_size(&self) -> f32 {
+            3.0 * this.radius * this.radius
+        }
+    }
+}
+
+class!{
+    #[meta]
+    #[rc]
+    #[register]
+    struct Node {
+        value: i32
+    }
+    impl Node {
+        fn describe(&self) -> String {
+            this.value.to_string()
+        }
+    }
+}
+
+class!{
+    extends Node;
+    struct Leaf {
+        label: String
+    }
+    impl Leaf {
+        fn describe(&self) -> String {
+            format!("{} {}", _super.describe(), this.label)
+        }
+    }
+}
+
+class!{
+    #[register]
+    struct Point {
+        x: i32
+    }
+    impl Point {
+        fn get_x(&self) -> i32 {
+            this.x
+        }
+    }
+}
+
+class!{
+    #[register]
+    struct Account {
+        balance: i64
+    }
+    impl Account {
+        fn get_balance(&self) -> i64 {
+            this.balance
+        }
+        #[validate]
+        fn check(&self) -> Result<(), String> {
+            if this.balance < 0 {
+                return Err(String::from("balance should not be negative"));
+            }
+            Ok(())
+        }
+    }
+}
+
+def_registry!();
@@ -0,0 +1,110 @@
+#![cfg(feature = "serde")]
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let car = Car::new(String::from("Car"), String::from("car1"), 4);
+    let json = serde_json::to_string(&*car).unwrap();
+    assert_eq!(&json, r#"{"vehicle_name":"Car","distance":0,"name":"car1","wheels":4}"#);
+
+    let car = Car::deserialize(&mut serde_json::Deserializer::from_str(r#"{"vehicle_name":"Car","distance":7,"name":"car2","wheels":3}"#)).unwrap();
+    assert_eq!(&car.describe(), "Car car2 with 3 wheels");
+    assert_eq!(car.get_distance(), 7);
+
+    let err = Car::deserialize(&mut serde_json::Deserializer::from_str(r#"{"vehicle_name":"Car","distance":7}"#));
+    assert!(err.is_err());
+
+    let car = Car::deserialize(&mut serde_json::Deserializer::from_str(r#"{"vehicle_name":"Car","name":"car3","wheels":4}"#)).unwrap();
+    assert_eq!(&car.describe(), "Car car3 with 4 wheels");
+    assert_eq!(car.get_distance(), 0);
+
+    let point = Point::deserialize(&mut serde_json::Deserializer::from_str(r#"{"x":1,"y":2}"#)).unwrap();
+    assert_eq!(point.sum(), 3);
+    assert_eq!(&serde_json::to_string(&*point).unwrap(), r#"{"x":1,"y":2}"#);
+}
+
+#[test]
+fn validate_test() {
+    let range = Range::deserialize(&mut serde_json::Deserializer::from_str(r#"{"start":1,"end":3}"#)).unwrap();
+    assert_eq!(range.len(), 2);
+    assert_eq!(range.count(), 2);
+
+    let range = Range::deserialize(&mut serde_json::Deserializer::from_str(r#"{"start":1,"end":5,"step":2}"#)).unwrap();
+    assert_eq!(range.count(), 2);
+
+    let err = Range::deserialize(&mut serde_json::Deserializer::from_str(r#"{"start":3,"end":1}"#)).err().unwrap();
+    assert!(err.to_string().starts_with(r#"failed to create Range: "start should not be greater than end""#));
+}
+
+class!{
+    #[derive(Serialize, Deserialize)]
+    struct Vehicle {
+        name: String,
+        #[default]
+        distance: u32
+    }
+    impl Vehicle {
+        fn get_name(&self) -> String {
+            this.name.clone()
+        }
+        fn describe(&self) -> String {
+            format!("{} {}", this.name, self.get_name())
+        }
+        fn get_distance(&self) -> u32 {
+            this.distance
+        }
+    }
+}
+
+class!{
+    extends Vehicle;
+    #[derive(Serialize, Deserialize)]
+    struct Car {
+        name: String,
+        wheels: u32
+    }
+    impl Car {
+        fn get_name(&self) -> String {
+            format!("{} with {} wheels", this.name, this.wheels)
+        }
+    }
+}
+
+class!{
+    #[derive(Serialize, Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32
+    }
+    impl Point {
+        fn sum(&self) -> i32 {
+            this.x + this.y
+        }
+    }
+}
+
+class!{
+    #[derive(Serialize, Deserialize)]
+    struct Range {
+        start: i32,
+        end: i32,
+        #[default = 1]
+        step: i32
+    }
+    impl Range {
+        fn len(&self) -> i32 {
+            this.end - this.start
+        }
+        fn count(&self) -> i32 {
+            self.len() / this.step
+        }
+        #[validate]
+        fn check(&self) -> Result<(), String> {
+            if this.start > this.end {
+                return Err(String::from("start should not be greater than end"));
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{cell::RefCell, rc::Rc, sync::{Arc, Mutex}};
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let button = Button::new(String::from("ok"));
+    assert_eq!(&button.name(), "Button ok");
+    assert_eq!(&button.describe(), "Button ok with 0 children");
+    assert_eq!(button.repeat_name(2), vec![String::from("Button ok"); 2]);
+
+    let panel = Widget::new();
+    panel.add(button.this_rc());
+    panel.add(button.clone());
+    assert_eq!(Rc::strong_count(&button), 3);
+    assert_eq!(&panel.describe(), "Widget with 2 children");
+    assert_eq!(&panel.get_children()[0].name(), "Button ok");
+
+    assert_eq!(button.click(), 1);
+    assert_eq!(button.click(), 2);
+    assert_eq!(button.this_weak().upgrade().unwrap().name(), "Button ok");
+
+    drop(panel);
+    assert_eq!(Rc::strong_count(&button), 1);
+    let weak = button.this_weak();
+    drop(button);
+    assert!(weak.upgrade().is_none());
+
+    let counter = Counter::new(1);
+    let shared = counter.clone();
+    shared.increase();
+    assert_eq!(counter.get(), 2);
+    assert_eq!(Arc::strong_count(&counter), 2);
+}
+
+class!{
+    #[rc]
+    struct Widget {
+        #[default]
+        children: RefCell<Vec<Rc<dyn __Widget__>>>
+    }
+    impl Widget {
+        fn name(&self) -> String {
+            String::from("Widget")
+        }
+        fn add(&self, child: Rc<dyn __Widget__>) {
+            this.children.borrow_mut().push(child);
+        }
+        fn get_children(&self) -> Vec<Rc<dyn __Widget__>> {
+            this.children.borrow().clone()
+        }
+        fn describe(&self) -> String {
+            format!("{} with {} children", self.name(), this.children.borrow().len())
+        }
+        fn repeat_name(&self, times: usize) -> Vec<String> {
+            let first = self.name();
+            (1..times).map(move |_| self.name()).chain(std::iter::once(first)).collect()
+        }
+    }
+}
+
+class!{
+    extends Widget;
+    struct Button {
+        label: String,
+        #[default]
+        clicked: RefCell<u32>
+    }
+    impl Button {
+        fn name(&self) -> String {
+            format!("Button {}", this.label)
+        }
+        fn click(&self) -> u32 {
+            *this.clicked.borrow_mut() += 1;
+            *this.clicked.borrow()
+        }
+    }
+}
+
+class!{
+    #[arc]
+    struct Counter {
+        count: Mutex<u32>
+    }
+    impl Counter {
+        #[constructor]
+        fn new(count: u32) {
+            Self { count: Mutex::new(count) }
+        }
+        fn increase(&self) {
+            *this.count.lock().unwrap() += 1;
+        }
+        fn get(&self) -> u32 {
+            *this.count.lock().unwrap()
+        }
+    }
+}
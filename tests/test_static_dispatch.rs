@@ -0,0 +1,112 @@
+use std::pin::Pin;
+
+use rust_oop::{class, def_as_mut};
+
+def_as_mut!();
+
+#[test]
+fn main_test() {
+    let mut square = Square::new(2.0);
+    assert_eq!(&square.name(), "Square");
+    assert_eq!(&square.describe(), "Square with size 4");
+    assert_eq!(&square.base_describe(), "Square with size 4");
+
+    as_mut!(square).scale(2.0);
+    assert_eq!(square.cal_size(), 16.0);
+    assert_eq!(square.get_scaled(), 1);
+
+    let shapes: Vec<&dyn __Shape__> = vec![&*square];
+    assert_eq!(&shapes[0].describe(), "Square with size 16");
+
+    let rectangle = Rectangle::new(2.0, 3.0);
+    assert_eq!(&rectangle.describe(), "Rectangle with size 6");
+
+    let tile = Tile::new(0, 1.0, 2.0);
+    assert_eq!(&tile.describe(), "Tile with size 2");
+}
+
+class!{
+    #[static_dispatch]
+    struct Shape {
+        scaled: u32
+    }
+    impl Shape {
+        fn name(&self) -> String {
+            String::from("Shape")
+        }
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+        fn describe(&self) -> String {
+            format!("{} with size {}", self.name(), self.cal_size())
+        }
+        #[allow(unused_variables)]
+        fn scale(&mut self, times: f32) {
+            this.scaled += 1;
+        }
+        fn get_scaled(&self) -> u32 {
+            this.scaled
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Rectangle {
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        #[constructor]
+        fn new(width: f32, height: f32) {
+            super_new!(0);
+            Self { width, height }
+        }
+        fn name(&self) -> String {
+            String::from("Rectangle")
+        }
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+        fn scale(&mut self, times: f32) {
+            this.width *= times;
+            this.height *= times;
+            _super_mut.scale(times);
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct Square { }
+    impl Square {
+        #[constructor]
+        fn new(len: f32) {
+            super_new!(len, len);
+            Self { }
+        }
+        fn name(&self) -> String {
+            String::from("Square")
+        }
+        fn base_describe(&self) -> String {
+            _super.describe()
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    #[inline_parent]
+    struct Tile {
+        width: f32,
+        height: f32
+    }
+    impl Tile {
+        fn name(&self) -> String {
+            String::from("Tile")
+        }
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+    }
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "serde")]
+
+use std::pin::Pin;
+
+use rust_oop::{class, tagged};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Scene {
+    #[serde(serialize_with = "Shape::serialize_dyn_vec", deserialize_with = "Shape::deserialize_dyn_vec")]
+    shapes: Vec<Pin<Box<dyn __Shape__>>>,
+}
+
+#[test]
+fn main_test() {
+    let json = r#"{"shapes":[{"Rectangle":{"color":"red","width":2.0,"height":3.0}},{"Square":{"color":"blue","width":2.0,"height":2.0,"rounded":true}},{"Shape":{"color":"green"}}]}"#;
+    let scene: Scene = serde_json::from_str(json).unwrap();
+    let described: Vec<String> = scene.shapes.iter().map(|x| x.describe()).collect();
+    assert_eq!(described, vec![
+        String::from("red Rectangle with size 6"),
+        String::from("blue rounded Square with size 4"),
+        String::from("green Shape with size 0"),
+    ]);
+    assert_eq!(&serde_json::to_string(&scene).unwrap(), json);
+
+    let shape = Shape::deserialize_dyn(&mut serde_json::Deserializer::from_str(r#"{"Square":{"color":"red","width":1.0,"height":1.0,"rounded":false}}"#)).unwrap();
+    assert_eq!(&shape.describe(), "red Square with size 1");
+
+    let err = serde_json::from_str::<Scene>(r#"{"shapes":[{"Circle":{"color":"red"}}]}"#).err().unwrap();
+    assert!(err.to_string().starts_with("unknown variant `Circle`, expected one of `Rectangle`, `Shape`, `Square`"));
+}
+
+class!{
+    #[derive(Serialize, Deserialize)]
+    struct Shape {
+        color: String
+    }
+    impl Shape {
+        fn name(&self) -> String {
+            String::from("Shape")
+        }
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+        fn describe(&self) -> String {
+            format!("{} {} with size {}", this.color, self.name(), self.cal_size())
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    #[derive(Serialize, Deserialize)]
+    struct Rectangle {
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        fn name(&self) -> String {
+            String::from("Rectangle")
+        }
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    #[derive(Serialize, Deserialize)]
+    struct Square {
+        rounded: bool
+    }
+    impl Square {
+        fn name(&self) -> String {
+            if this.rounded {
+                String::from("rounded Square")
+            } else {
+                String::from("Square")
+            }
+        }
+    }
+}
+
+tagged!(Shape);
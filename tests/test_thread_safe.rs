@@ -0,0 +1,87 @@
+use std::{sync::{Arc, Mutex}, thread};
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let car = Car::new(String::from("Car"), String::from("car1"));
+    let car = thread::spawn(move || {
+        assert_eq!(&car.describe(), "Car car1");
+        car
+    }).join().unwrap();
+
+    let car = Arc::new(car);
+    let handles: Vec<_> = (0..4).map(|_| {
+        let car = car.clone();
+        thread::spawn(move || car.drive(1))
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(car.get_distance(), 4);
+
+    let vehicles: Vec<&dyn __Vehicle__> = vec![&**car];
+    thread::scope(|scope| {
+        scope.spawn(|| assert_eq!(&vehicles[0].describe(), "Car car1"));
+    });
+
+    let counter = Counter::new();
+    let shared = counter.clone();
+    thread::spawn(move || shared.increase()).join().unwrap();
+    assert_eq!(counter.get(), 1);
+}
+
+class!{
+    #[thread_safe]
+    struct Vehicle {
+        _type: String,
+        distance: Mutex<u32>
+    }
+    impl Vehicle {
+        #[constructor]
+        fn new(_type: String) {
+            Self { _type, distance: Mutex::new(0) }
+        }
+        fn get_name(&self) -> String {
+            String::from("unnamed")
+        }
+        fn describe(&self) -> String {
+            format!("{} {}", this._type, self.get_name())
+        }
+        fn drive(&self, distance: u32) {
+            *this.distance.lock().unwrap() += distance;
+        }
+        fn get_distance(&self) -> u32 {
+            *this.distance.lock().unwrap()
+        }
+    }
+}
+
+class!{
+    extends Vehicle;
+    struct Car {
+        name: String
+    }
+    impl Car {
+        fn get_name(&self) -> String {
+            this.name.clone()
+        }
+    }
+}
+
+class!{
+    #[arc]
+    #[thread_safe]
+    struct Counter {
+        #[default]
+        count: Mutex<u32>
+    }
+    impl Counter {
+        fn increase(&self) {
+            *this.count.lock().unwrap() += 1;
+        }
+        fn get(&self) -> u32 {
+            *this.count.lock().unwrap()
+        }
+    }
+}
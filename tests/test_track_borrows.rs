@@ -0,0 +1,91 @@
+use std::pin::Pin;
+
+use rust_oop::{class, def_as_mut};
+
+def_as_mut!();
+
+#[test]
+fn main_test() {
+    let mut rectangle = Rectangle::new(0, 2.0, 3.0);
+    assert_eq!(&rectangle.describe(), "Rectangle with size 6");
+    as_mut!(rectangle).scale(2.0);
+    assert_eq!(rectangle.cal_size(), 24.0);
+    assert_eq!(rectangle.get_scaled(), 1);
+    as_mut!(rectangle).scale_twice(1.0);
+    assert_eq!(rectangle.get_scaled(), 3);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Shape::reset can not borrow `self_mut` while it is borrowed by Shape::reset")]
+fn mutable_in_shared_test() {
+    let rectangle = Rectangle::new(0, 2.0, 3.0);
+    rectangle.reset();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Shape::describe_mut can not borrow `self` while it is mutably borrowed by Shape::scale_describe")]
+fn shared_in_mutable_test() {
+    let mut rectangle = Rectangle::new(0, 2.0, 3.0);
+    as_mut!(rectangle).scale_describe();
+}
+
+class!{
+    #[track_borrows]
+    struct Shape {
+        scaled: u32
+    }
+    impl Shape {
+        fn name(&self) -> String {
+            String::from("Shape")
+        }
+        fn cal_size(&self) -> f32 {
+            0.0
+        }
+        fn describe(&self) -> String {
+            format!("{} with size {}", self.name(), self.cal_size())
+        }
+        #[allow(unused_variables)]
+        fn scale(&mut self, times: f32) {
+            this.scaled += 1;
+        }
+        fn scale_twice(&mut self, times: f32) {
+            self_mut.scale(times);
+            self_mut.scale(times);
+        }
+        fn get_scaled(&self) -> u32 {
+            this.scaled
+        }
+        fn reset(&self) {
+            self_mut.scale(0.0);
+        }
+        fn scale_describe(&mut self) {
+            self_mut.describe_mut();
+        }
+        fn describe_mut(&mut self) {
+            self.describe();
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Rectangle {
+        width: f32,
+        height: f32
+    }
+    impl Rectangle {
+        fn name(&self) -> String {
+            String::from("Rectangle")
+        }
+        fn cal_size(&self) -> f32 {
+            this.width * this.height
+        }
+        fn scale(&mut self, times: f32) {
+            this.width *= times;
+            this.height *= times;
+            _super_mut.scale(times);
+        }
+    }
+}
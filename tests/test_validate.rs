@@ -0,0 +1,214 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rust_oop::class;
+
+#[test]
+fn main_test() {
+    let square = Square::try_new(1.0, 2.0, 2.0, Rc::new(())).unwrap();
+    assert_eq!(square.cal_size(), 4.0);
+
+    let err = Square::try_new(-1.0, -2.0, 2.0, Rc::new(())).err();
+    assert_eq!(err, Some(String::from("scale should be positive")));
+
+    let counter = Rc::new(());
+    let err = Square::try_new(1.0, -2.0, 2.0, counter.clone()).err();
+    assert_eq!(err, Some(String::from("width should be positive")));
+    assert_eq!(Rc::strong_count(&counter), 1);
+
+    let err = Square::try_new(1.0, 2.0, 3.0, counter.clone()).err();
+    assert_eq!(err, Some(String::from("square should have the same width and height")));
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+fn constructor_test() {
+    let circle = Circle::try_new(1.0, 2.0).unwrap();
+    assert_eq!(circle.cal_size(), 12.0);
+    let circle = Circle::try_with_diameter(2.0).unwrap();
+    assert_eq!(circle.cal_size(), 3.0);
+
+    assert_eq!(Circle::try_new(1.0, -2.0).err(), Some(String::from("radius should be positive")));
+    assert_eq!(Circle::try_new(-1.0, 2.0).err(), Some(String::from("scale should be positive")));
+    assert_eq!(Circle::try_with_diameter(0.0).err(), Some(String::from("radius should be positive")));
+}
+
+#[test]
+#[should_panic(expected = "failed to create Circle")]
+fn constructor_panic_test() {
+    Circle::with_diameter(-2.0);
+}
+
+#[test]
+fn order_test() {
+    take_log();
+    Branch::try_new(1).unwrap();
+    assert_eq!(take_log(), ["Node", "Branch"]);
+    Branch::try_with_level(1).unwrap();
+    assert_eq!(take_log(), ["Node", "Branch"]);
+    Branch::builder().level(1).build().unwrap();
+    assert_eq!(take_log(), ["Node", "Branch"]);
+    Leaf::try_new(1).unwrap();
+    assert_eq!(take_log(), ["Node", "Branch", "Leaf"]);
+
+    assert!(Leaf::try_new(-1).is_err());
+    assert_eq!(take_log(), ["Node"]);
+    match Branch::builder().level(-1).build() {
+        Err(BranchBuildError::Invalid(err)) => assert_eq!(err, "level should not be negative"),
+        _ => panic!("Branch should be invalid"),
+    }
+    assert_eq!(take_log(), ["Node"]);
+}
+
+#[test]
+#[should_panic(expected = "failed to create Rectangle")]
+fn panic_test() {
+    Rectangle::new(1.0, 2.0, -2.0, Rc::new(()));
+}
+
+class!{
+    struct Shape {
+        scale: f32
+    }
+    impl Shape {
+        #[validate]
+        fn check(&self) -> Result<(), String> {
+            if this.scale <= 0.0 {
+                return Err(String::from("scale should be positive"));
+            }
+            Ok(())
+        }
+        fn cal_size(&self) -> f32 {
+            unimplemented!();
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Rectangle {
+        width: f32,
+        height: f32,
+        #[allow(dead_code)]
+        counter: Rc<()>
+    }
+    impl Rectangle {
+        #[validate]
+        fn check(&self) -> Result<(), String> {
+            if this.width <= 0.0 {
+                return Err(String::from("width should be positive"));
+            }
+            if this.height <= 0.0 {
+                return Err(String::from("height should be positive"));
+            }
+            Ok(())
+        }
+        fn cal_size(&self) -> f32 {
+            _super.scale * this.width * this.height
+        }
+    }
+}
+
+class!{
+    extends Rectangle;
+    struct Square { }
+    impl Square {
+        #[validate]
+        fn check(&self) -> Result<(), String> {
+            if _super.width != _super.height {
+                return Err(String::from("square should have the same width and height"));
+            }
+            Ok(())
+        }
+    }
+}
+
+class!{
+    extends Shape;
+    struct Circle {
+        radius: f32
+    }
+    impl Circle {
+        #[constructor]
+        fn new(scale: f32, radius: f32) {
+            super_new!(scale);
+            Self { radius }
+        }
+        #[constructor]
+        fn with_diameter(diameter: f32) {
+            super_new!(1.0);
+            Self { radius: diameter / 2.0 }
+        }
+        #[validate]
+        fn check(&self) -> Result<(), String> {
+            if this.radius <= 0.0 {
+                return Err(String::from("radius should be positive"));
+            }
+            Ok(())
+        }
+        fn cal_size(&self) -> f32 {
+            _super.scale * 3.0 * this.radius * this.radius
+        }
+    }
+}
+
+thread_local! {
+    static LOG: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// names of classes whose validators ran since the last call.
+fn take_log() -> Vec<&'static str> {
+    LOG.with(|x| x.take())
+}
+
+class!{
+    struct Node {
+        level: i32
+    }
+    impl Node {
+        #[validate]
+        fn check_node(&self) -> Result<(), String> {
+            LOG.with(|x| x.borrow_mut().push("Node"));
+            if this.level < 0 {
+                return Err(String::from("level should not be negative"));
+            }
+            Ok(())
+        }
+    }
+}
+
+class!{
+    extends Node;
+    #[builder]
+    struct Branch {
+        #[default = 1]
+        width: i32
+    }
+    impl Branch {
+        #[constructor]
+        fn with_level(level: i32) {
+            super_new!(level);
+            Self { }
+        }
+        #[validate]
+        fn check_branch(&self) -> Result<(), String> {
+            LOG.with(|x| x.borrow_mut().push("Branch"));
+            if this.width <= 0 {
+                return Err(String::from("width should be positive"));
+            }
+            Ok(())
+        }
+    }
+}
+
+class!{
+    extends Branch;
+    #[inline_parent]
+    struct Leaf { }
+    impl Leaf {
+        #[validate]
+        fn check_leaf(&self) -> Result<(), String> {
+            LOG.with(|x| x.borrow_mut().push("Leaf"));
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use std::future::Ready;
+
+use rust_oop::class;
+
+class!{
+    struct Task {
+        name: String,
+        #[pin]
+        result: Ready<u32>
+    }
+    impl Task {
+        fn get_name(&self) -> String {
+            this.name.clone()
+        }
+    }
+}
+
+impl Unpin for Task {}
+
+fn main() {}
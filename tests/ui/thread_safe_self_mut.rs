@@ -0,0 +1,30 @@
+use rust_oop::class;
+
+class!{
+    #[thread_safe]
+    struct Counter {
+        count: u32
+    }
+    impl Counter {
+        fn reset(&self) {
+            self_mut.count = 0;
+        }
+        fn increase(&mut self) {
+            this.count += 1;
+        }
+    }
+}
+
+class!{
+    extends Counter;
+    struct StepCounter {
+        step: u32
+    }
+    impl StepCounter {
+        fn set_step(&self, step: u32) {
+            self_mut.step = step;
+        }
+    }
+}
+
+fn main() {}